@@ -0,0 +1,133 @@
+//! Resolve the exact version of every dependency from `Cargo.lock`.
+//!
+//! The resolved packages are written to `$OUT_DIR/deps.toml`, which is then
+//! embedded by `src/deps.rs`.
+
+use std::{collections::BTreeMap, env, error::Error, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DependencySpec {
+    String(String),
+    Object {
+        package: Option<String>,
+        version: Option<String>,
+    },
+}
+
+impl DependencySpec {
+    fn package<'a>(&'a self, key: &'a str) -> &'a str {
+        match self {
+            Self::Object {
+                package: Some(package),
+                ..
+            } => package,
+            _ => key,
+        }
+    }
+
+    fn version_req(&self) -> Option<&str> {
+        match self {
+            Self::String(version) => Some(version),
+            Self::Object { version, .. } => version.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoToml {
+    dependencies: BTreeMap<String, DependencySpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoLock {
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResolvedPackage<'a> {
+    package: &'a str,
+    version: &'a str,
+    source: Option<&'a str>,
+    checksum: Option<&'a str>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    let cargo_toml: CargoToml = toml::from_str(&fs::read_to_string("Cargo.toml")?)?;
+    let cargo_lock: CargoLock = toml::from_str(&fs::read_to_string("Cargo.lock")?)?;
+
+    let root = cargo_lock
+        .package
+        .iter()
+        .find(|p| p.name == env!("CARGO_PKG_NAME") && p.source.is_none())
+        .ok_or("root package not found in Cargo.lock")?;
+
+    let mut resolved = BTreeMap::new();
+
+    for (key, spec) in &cargo_toml.dependencies {
+        let name = spec.package(key);
+
+        // Entries are either `name` or `name version` (followed by the source
+        // if the same version comes from several sources).
+        let candidates = root
+            .dependencies
+            .iter()
+            .filter_map(|entry| {
+                let mut parts = entry.split_whitespace();
+                (parts.next() == Some(name)).then(|| parts.next())
+            })
+            .flat_map(|version| {
+                cargo_lock
+                    .package
+                    .iter()
+                    .filter(move |p| p.name == name && version.is_none_or(|v| p.version == v))
+            })
+            .collect::<Vec<_>>();
+
+        let locked = match candidates.as_slice() {
+            [] => return Err(format!("dependency `{key}` not found in Cargo.lock").into()),
+            [locked] => *locked,
+            _ => {
+                let req = semver::VersionReq::parse(spec.version_req().unwrap_or("*"))?;
+
+                candidates
+                    .into_iter()
+                    .find(|p| {
+                        semver::Version::parse(&p.version)
+                            .is_ok_and(|version| req.matches(&version))
+                    })
+                    .ok_or_else(|| format!("no locked version of `{key}` matches `{req}`"))?
+            }
+        };
+
+        resolved.insert(
+            key.replace('-', "_"),
+            ResolvedPackage {
+                package: &locked.name,
+                version: &locked.version,
+                source: locked.source.as_deref(),
+                checksum: locked.checksum.as_deref(),
+            },
+        );
+    }
+
+    let out = PathBuf::from(env::var("OUT_DIR")?).join("deps.toml");
+    fs::write(out, toml::to_string(&resolved)?)?;
+
+    Ok(())
+}
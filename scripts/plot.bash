@@ -7,13 +7,5 @@ DATA_DIR=results
 
 cargo build --release
 
-function plot {
-    cat "$DATA_DIR/$1.csv" | "$BIN" plot "$DATA_DIR" "$1"
-}
-
-plot ReadHeavy.std
-plot Exchange.std
-plot RapidGrow.std
-plot ReadHeavy.ahash
-plot Exchange.ahash
-plot RapidGrow.ahash
+# Regenerate the plots of every CSV in place.
+"$BIN" plot "$DATA_DIR"
@@ -1,10 +1,17 @@
+#[allow(unused_imports)]
+pub use self::btreemap::{ParkingLotRwLockBTreeMapTable, StdRwLockBTreeMapTable};
 pub use self::{
-    btreemap::ParkingLotRwLockBTreeMapTable, btreemap::StdRwLockBTreeMapTable,
     chashmap::CHashMapTable, contrie::ContrieTable, crossbeam_skiplist::CrossbeamSkipMapTable,
-    dashmap::DashMap5Table, dashmap::DashMapTable, evmap::EvmapTable, flurry::FlurryTable,
-    hashlink::ParkingLotRwLockHashLinkMapTable, hashlink::StdRwLockHashLinkMapTable,
-    papaya::PapayaTable, scc::SccIndexTable, scc::SccMapTable,
-    std::ParkingLotRwLockStdHashMapTable, std::StdRwLockStdHashMapTable,
+    dashmap::DashMap5Table, dashmap::DashMapTable, dashmap::ShardAmountDashMap5Table,
+    dashmap::ShardAmountDashMapTable, evmap::EvmapTable, flurry::FlurryGuardedTable,
+    flurry::FlurryTable, hashlink::ParkingLotMutexHashLinkLruTable,
+    hashlink::ParkingLotRwLockHashLinkMapTable, hashlink::StdMutexHashLinkLruTable,
+    hashlink::StdRwLockHashLinkMapTable, papaya::PapayaGuardedTable, papaya::PapayaTable,
+    scc::SccCacheTable, scc::SccIndexTable, scc::SccMapTable, sharded::Padded,
+    sharded::ParkingLotMutexShard, sharded::ParkingLotRwLockShard, sharded::ShardedTable,
+    sharded::StdMutexShard, sharded::StdRwLockShard, std::ParkingLotMutexStdHashMapTable,
+    std::ParkingLotRwLockStdHashMapTable, std::SpinLockStdHashMapTable,
+    std::StdMutexStdHashMapTable, std::StdRwLockStdHashMapTable,
 };
 
 mod btreemap;
@@ -17,14 +24,18 @@ mod flurry;
 mod hashlink;
 mod papaya;
 mod scc;
+mod sharded;
+mod spinlock;
 mod std;
 
 mod prelude {
     pub(crate) use std::sync::Arc;
 
+    pub(crate) use super::{flush_crossbeam_epoch, spinlock::SpinLock, GuardRefresh};
     pub(crate) use crate::{impl_collection, table};
 
     pub(crate) type Value = u32;
+    pub(crate) type StdMutex<T> = ::std::sync::Mutex<T>;
     pub(crate) type StdRwLock<T> = ::std::sync::RwLock<T>;
     pub(crate) type ParkingLotMutex<T> = ::parking_lot::Mutex<T>;
     pub(crate) type ParkingLotRwLock<T> = ::parking_lot::RwLock<T>;
@@ -47,6 +58,57 @@ pub(super) trait HasherT:
 
 impl<T> HasherT for T where T: Send + Sync + Clone + Default + ::std::hash::BuildHasher + 'static {}
 
+/// Teardown hook of a table, called once the workload has finished and every
+/// handle has been dropped.
+pub(crate) trait Reclaim: Sized {
+    /// Drop the table and deterministically reclaim the garbage it retired.
+    ///
+    /// Tables without deferred reclamation are simply dropped.
+    fn reclaim(self) {
+        drop(self);
+    }
+}
+
+/// Flush the garbage retired to the global crossbeam-epoch collector.
+pub(super) fn flush_crossbeam_epoch() {
+    let mut guard = crossbeam_epoch::pin();
+
+    guard.flush();
+
+    for _ in 0..32 {
+        guard.repin();
+    }
+}
+
+/// Counts the operations run on a long-lived guard, which has to be refreshed
+/// every so often for the garbage retired meanwhile to be reclaimed.
+pub(crate) struct GuardRefresh {
+    every: usize,
+    ops: usize,
+}
+
+impl GuardRefresh {
+    /// Refresh every `every` operations.
+    pub(crate) fn new(every: usize) -> Self {
+        assert!(every > 0, "guard refresh interval must be positive");
+
+        Self { every, ops: 0 }
+    }
+
+    /// Count an operation, and return whether the guard is due for a refresh
+    /// before running it.
+    #[inline]
+    pub(crate) fn tick(&mut self) -> bool {
+        if self.ops == self.every {
+            self.ops = 1;
+            true
+        } else {
+            self.ops += 1;
+            false
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! table {
     ($inner:ident, $value:ty, <K $(,$hasher:ident)?>, NOARC) => {
@@ -86,6 +148,44 @@ macro_rules! table {
 #[macro_export]
 macro_rules! impl_collection {
     (|K $(,$hasher:ident)?| $ty:ty;
+        with_capacity | $capacity:ident| $with_capacity:block;
+        reclaim |$reclaim_self:ident| $reclaim:block;
+        $($name:ident |$self:ident, $key:ident| $block:block);+
+    ) => {
+        $crate::impl_collection!(@collection |K $(,$hasher)?| $ty;
+            with_capacity |$capacity| $with_capacity;
+            $($name |$self, $key| $block);+
+        );
+
+        impl<K $(,$hasher)?> $crate::adapters::Reclaim for $ty
+        where
+            K: $crate::adapters::KeyT,
+            $($hasher: $crate::adapters::HasherT,)?
+        {
+            fn reclaim($reclaim_self: Self) {
+                $reclaim
+            }
+        }
+    };
+
+    (|K $(,$hasher:ident)?| $ty:ty;
+        with_capacity | $capacity:ident| $with_capacity:block;
+        $($name:ident |$self:ident, $key:ident| $block:block);+
+    ) => {
+        $crate::impl_collection!(@collection |K $(,$hasher)?| $ty;
+            with_capacity |$capacity| $with_capacity;
+            $($name |$self, $key| $block);+
+        );
+
+        impl<K $(,$hasher)?> $crate::adapters::Reclaim for $ty
+        where
+            K: $crate::adapters::KeyT,
+            $($hasher: $crate::adapters::HasherT,)?
+        {
+        }
+    };
+
+    (@collection |K $(,$hasher:ident)?| $ty:ty;
         with_capacity | $capacity:ident| $with_capacity:block;
         $($name:ident |$self:ident, $key:ident| $block:block);+
     ) => {
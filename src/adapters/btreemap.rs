@@ -1,3 +1,6 @@
+// BTreeMap based tables are not benchmarked by default, see `main.rs`.
+#![allow(dead_code)]
+
 use std::collections::BTreeMap;
 
 use super::prelude::*;
@@ -9,6 +9,18 @@ impl_collection! {
     with_capacity |_capacity| {
         Contrie::with_hasher(H::default())
     };
+    reclaim |self| {
+        drop(self);
+
+        // contrie retires to the global collector of an older crossbeam-epoch.
+        let mut guard = crossbeam_epoch7::pin();
+
+        guard.flush();
+
+        for _ in 0..32 {
+            guard.repin();
+        }
+    };
     get |self, key|  {
         self.0.get(key).is_some()
     };
@@ -9,6 +9,10 @@ impl_collection! {
     with_capacity |_capacity| {
         CrossbeamSkipMap::new()
     };
+    reclaim |self| {
+        drop(self);
+        flush_crossbeam_epoch();
+    };
     get |self, key|  {
         self.0.get(key).is_some()
     };
@@ -1,7 +1,7 @@
 use dashmap::DashMap;
 use dashmap5::DashMap as DashMap5;
 
-use super::prelude::*;
+use super::{prelude::*, HasherT, KeyT, Reclaim};
 
 table!(DashMap, Value, <K, H>);
 
@@ -43,4 +43,67 @@ impl_collection! {
     update |self, key| {
         self.0.get_mut(key).map(|mut v| *v += 1).is_some()
     }
-}
\ No newline at end of file
+}
+
+/// A table of the dashmap `$map` with `SHARDS` shards, a power of two greater
+/// than one, rather than a shard amount derived from the CPU count.
+macro_rules! shard_amount_table {
+    ($table:ident, $map:ident) => {
+        pub struct $table<K: KeyT, H: HasherT, const SHARDS: usize>(Arc<$map<K, Value, H>>);
+
+        impl<K: KeyT, H: HasherT, const SHARDS: usize> Clone for $table<K, H, SHARDS> {
+            fn clone(&self) -> Self {
+                Self(self.0.clone())
+            }
+        }
+
+        impl<K: KeyT, H: HasherT, const SHARDS: usize> bustle::Collection for $table<K, H, SHARDS> {
+            type Handle = Self;
+
+            #[inline]
+            fn with_capacity(capacity: usize) -> Self {
+                Self(Arc::new($map::with_capacity_and_hasher_and_shard_amount(
+                    capacity,
+                    H::default(),
+                    SHARDS,
+                )))
+            }
+
+            #[inline]
+            fn pin(&self) -> Self::Handle {
+                self.clone()
+            }
+        }
+
+        impl<K: KeyT, H: HasherT, const SHARDS: usize> bustle::CollectionHandle
+            for $table<K, H, SHARDS>
+        {
+            type Key = K;
+
+            #[inline]
+            fn get(&mut self, key: &Self::Key) -> bool {
+                self.0.get(key).is_some()
+            }
+
+            #[inline]
+            fn insert(&mut self, key: &Self::Key) -> bool {
+                self.0.insert(*key, 0).is_none()
+            }
+
+            #[inline]
+            fn remove(&mut self, key: &Self::Key) -> bool {
+                self.0.remove(key).is_some()
+            }
+
+            #[inline]
+            fn update(&mut self, key: &Self::Key) -> bool {
+                self.0.get_mut(key).map(|mut v| *v += 1).is_some()
+            }
+        }
+
+        impl<K: KeyT, H: HasherT, const SHARDS: usize> Reclaim for $table<K, H, SHARDS> {}
+    };
+}
+
+shard_amount_table!(ShardAmountDashMapTable, DashMap);
+shard_amount_table!(ShardAmountDashMap5Table, DashMap5);
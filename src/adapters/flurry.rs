@@ -1,20 +1,31 @@
+use std::mem;
+
 use flurry::HashMap as Flurry;
-use seize::Collector;
+use seize::{Collector, Guard};
 
-use super::prelude::*;
+use super::{prelude::*, HasherT, KeyT, Reclaim};
 
 const BATCH_SIZE: usize = 2000;
 
+fn flurry<K: KeyT, H: HasherT>(capacity: usize) -> Flurry<K, Value, H> {
+    Flurry::with_capacity_and_hasher(capacity, H::default()).with_collector(
+        Collector::new()
+            .epoch_frequency(None)
+            .batch_size(BATCH_SIZE),
+    )
+}
+
 table!(Flurry, Value, <K, H>);
 
 impl_collection! {
     |K, H| FlurryTable<K, H>;
     with_capacity |capacity| {
-        Flurry::with_capacity_and_hasher(capacity, H::default()).with_collector(
-            Collector::new()
-                .epoch_frequency(None)
-                .batch_size(BATCH_SIZE),
-        )
+        flurry(capacity)
+    };
+    reclaim |self| {
+        // The collector is owned by the map and reclaims every retired batch,
+        // including those of exited threads, when dropped.
+        drop(self);
     };
     get |self, key|  {
         self.0.pin().get(key).is_some()
@@ -32,3 +43,86 @@ impl_collection! {
             .is_some()
     }
 }
+
+/// A flurry map whose handles hold a guard of their own and refresh it every
+/// `EVERY` operations, instead of pinning on every operation.
+pub struct FlurryGuardedTable<K: KeyT, H: HasherT, const EVERY: usize>(Arc<Flurry<K, Value, H>>);
+
+impl<K: KeyT, H: HasherT, const EVERY: usize> Clone for FlurryGuardedTable<K, H, EVERY> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+pub struct FlurryGuardedHandle<K: KeyT, H: HasherT> {
+    // Declared first to be dropped before the map it borrows from.
+    guard: Guard<'static>,
+    map: Arc<Flurry<K, Value, H>>,
+    refresh: GuardRefresh,
+}
+
+impl<K: KeyT, H: HasherT> FlurryGuardedHandle<K, H> {
+    #[inline]
+    fn refresh(&mut self) {
+        if self.refresh.tick() {
+            self.guard.refresh();
+        }
+    }
+}
+
+impl<K: KeyT, H: HasherT, const EVERY: usize> bustle::Collection
+    for FlurryGuardedTable<K, H, EVERY>
+{
+    type Handle = FlurryGuardedHandle<K, H>;
+
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self(Arc::new(flurry(capacity)))
+    }
+
+    #[inline]
+    fn pin(&self) -> Self::Handle {
+        let map = self.0.clone();
+        // SAFETY: the guard borrows the collector of the map, which lives on
+        // the heap until the handle drops its reference after the guard.
+        let guard = unsafe { mem::transmute::<Guard<'_>, Guard<'static>>(map.guard()) };
+
+        FlurryGuardedHandle {
+            guard,
+            map,
+            refresh: GuardRefresh::new(EVERY),
+        }
+    }
+}
+
+impl<K: KeyT, H: HasherT> bustle::CollectionHandle for FlurryGuardedHandle<K, H> {
+    type Key = K;
+
+    #[inline]
+    fn get(&mut self, key: &Self::Key) -> bool {
+        self.refresh();
+        self.map.get(key, &self.guard).is_some()
+    }
+
+    #[inline]
+    fn insert(&mut self, key: &Self::Key) -> bool {
+        self.refresh();
+        self.map.insert(*key, 0, &self.guard).is_none()
+    }
+
+    #[inline]
+    fn remove(&mut self, key: &Self::Key) -> bool {
+        self.refresh();
+        self.map.remove(key, &self.guard).is_some()
+    }
+
+    #[inline]
+    fn update(&mut self, key: &Self::Key) -> bool {
+        self.refresh();
+        self.map
+            .compute_if_present(key, |_, v| Some(v + 1), &self.guard)
+            .is_some()
+    }
+}
+
+impl<K: KeyT, H: HasherT, const EVERY: usize> Reclaim for FlurryGuardedTable<K, H, EVERY> {}
@@ -1,6 +1,6 @@
 use hashlink::LinkedHashMap as HashLinkMap;
 
-use super::prelude::*;
+use super::{prelude::*, HasherT, KeyT};
 
 table!(StdRwLock, HashLinkMap, Value, <K, H>);
 
@@ -47,3 +47,78 @@ impl_collection! {
         self.0.write().get_mut(key).map(|v| *v += 1).is_some()
     }
 }
+
+/// A `LinkedHashMap` used as a bounded LRU cache, its front being the least
+/// recently used entry.
+pub struct Lru<M> {
+    capacity: usize,
+    map: M,
+}
+
+#[derive(Clone)]
+pub struct StdMutexHashLinkLruTable<K: KeyT, H: HasherT>(
+    Arc<Lru<StdMutex<HashLinkMap<K, Value, H>>>>,
+);
+
+impl_collection! {
+    |K, H| StdMutexHashLinkLruTable<K, H>;
+    with_capacity |capacity| {
+        Lru {
+            capacity,
+            map: StdMutex::new(HashLinkMap::with_capacity_and_hasher(capacity + 1, H::default())),
+        }
+    };
+    get |self, key|  {
+        self.0.map.lock().unwrap().to_back(key).is_some()
+    };
+    insert |self, key| {
+        let mut map = self.0.map.lock().unwrap();
+        let inserted = map.insert(*key, 0).is_none();
+
+        if map.len() > self.0.capacity {
+            map.pop_front();
+        }
+
+        inserted
+    };
+    remove |self, key| {
+        self.0.map.lock().unwrap().remove(key).is_some()
+    };
+    update |self, key| {
+        self.0.map.lock().unwrap().to_back(key).map(|v| *v += 1).is_some()
+    }
+}
+
+#[derive(Clone)]
+pub struct ParkingLotMutexHashLinkLruTable<K: KeyT, H: HasherT>(
+    Arc<Lru<ParkingLotMutex<HashLinkMap<K, Value, H>>>>,
+);
+
+impl_collection! {
+    |K, H| ParkingLotMutexHashLinkLruTable<K, H>;
+    with_capacity |capacity| {
+        Lru {
+            capacity,
+            map: ParkingLotMutex::new(HashLinkMap::with_capacity_and_hasher(capacity + 1, H::default())),
+        }
+    };
+    get |self, key|  {
+        self.0.map.lock().to_back(key).is_some()
+    };
+    insert |self, key| {
+        let mut map = self.0.map.lock();
+        let inserted = map.insert(*key, 0).is_none();
+
+        if map.len() > self.0.capacity {
+            map.pop_front();
+        }
+
+        inserted
+    };
+    remove |self, key| {
+        self.0.map.lock().remove(key).is_some()
+    };
+    update |self, key| {
+        self.0.map.lock().to_back(key).map(|v| *v += 1).is_some()
+    }
+}
@@ -1,23 +1,35 @@
-use papaya::HashMap as Papaya;
+use std::mem;
 
-use super::prelude::*;
+use papaya::{Guard, HashMap as Papaya};
+use seize4::OwnedGuard;
+
+use super::{prelude::*, HasherT, KeyT, Reclaim};
 
 const BATCH_SIZE: usize = 2000;
 
+fn papaya<K: KeyT, H: HasherT>(capacity: usize) -> Papaya<K, Value, H> {
+    papaya::HashMap::builder()
+        .capacity(capacity)
+        .hasher(H::default())
+        .collector(
+            papaya::Collector::new()
+                .epoch_frequency(None)
+                .batch_size(BATCH_SIZE),
+        )
+        .build()
+}
+
 table!(Papaya, Value, <K, H>);
 
 impl_collection! {
     |K, H| PapayaTable<K, H>;
     with_capacity |capacity| {
-        papaya::HashMap::builder()
-            .capacity(capacity)
-            .hasher(H::default())
-            .collector(
-                papaya::Collector::new()
-                    .epoch_frequency(None)
-                    .batch_size(BATCH_SIZE),
-            )
-            .build()
+        papaya(capacity)
+    };
+    reclaim |self| {
+        // The collector is owned by the map and reclaims every retired batch,
+        // including those of exited threads, when dropped.
+        drop(self);
     };
     get |self, key|  {
         self.0.pin().get(key).is_some()
@@ -32,3 +44,85 @@ impl_collection! {
         self.0.pin().update(*key, |v| v + 1).is_some()
     }
 }
+
+/// A papaya map whose handles pin once and refresh their guard every `EVERY`
+/// operations, instead of pinning on every operation.
+pub struct PapayaGuardedTable<K: KeyT, H: HasherT, const EVERY: usize>(Arc<Papaya<K, Value, H>>);
+
+impl<K: KeyT, H: HasherT, const EVERY: usize> Clone for PapayaGuardedTable<K, H, EVERY> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+pub struct PapayaGuardedHandle<K: KeyT, H: HasherT> {
+    // Declared first to be dropped before the map it borrows from.
+    guard: OwnedGuard<'static>,
+    map: Arc<Papaya<K, Value, H>>,
+    refresh: GuardRefresh,
+}
+
+impl<K: KeyT, H: HasherT> PapayaGuardedHandle<K, H> {
+    #[inline]
+    fn refresh(&mut self) {
+        if self.refresh.tick() {
+            self.guard.refresh();
+        }
+    }
+}
+
+impl<K: KeyT, H: HasherT, const EVERY: usize> bustle::Collection
+    for PapayaGuardedTable<K, H, EVERY>
+{
+    type Handle = PapayaGuardedHandle<K, H>;
+
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self(Arc::new(papaya(capacity)))
+    }
+
+    #[inline]
+    fn pin(&self) -> Self::Handle {
+        let map = self.0.clone();
+        // SAFETY: the guard borrows the collector of the map, which lives on
+        // the heap until the handle drops its reference after the guard.
+        let guard =
+            unsafe { mem::transmute::<OwnedGuard<'_>, OwnedGuard<'static>>(map.owned_guard()) };
+
+        PapayaGuardedHandle {
+            guard,
+            map,
+            refresh: GuardRefresh::new(EVERY),
+        }
+    }
+}
+
+impl<K: KeyT, H: HasherT> bustle::CollectionHandle for PapayaGuardedHandle<K, H> {
+    type Key = K;
+
+    #[inline]
+    fn get(&mut self, key: &Self::Key) -> bool {
+        self.refresh();
+        self.map.get(key, &self.guard).is_some()
+    }
+
+    #[inline]
+    fn insert(&mut self, key: &Self::Key) -> bool {
+        self.refresh();
+        self.map.insert(*key, 0, &self.guard).is_none()
+    }
+
+    #[inline]
+    fn remove(&mut self, key: &Self::Key) -> bool {
+        self.refresh();
+        self.map.remove(key, &self.guard).is_some()
+    }
+
+    #[inline]
+    fn update(&mut self, key: &Self::Key) -> bool {
+        self.refresh();
+        self.map.update(*key, |v| v + 1, &self.guard).is_some()
+    }
+}
+
+impl<K: KeyT, H: HasherT, const EVERY: usize> Reclaim for PapayaGuardedTable<K, H, EVERY> {}
@@ -1,7 +1,44 @@
-use scc::{hash_map::HashMap as SccMap, HashIndex as SccIndex, LinkedList};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use scc::{ebr::Guard, hash_map::HashMap as SccMap, HashCache as SccCache, HashIndex as SccIndex};
 
 use super::prelude::*;
 
+/// The epochs a thread witnesses before the entries it retired are freed.
+const EPOCHS_TO_RECLAIM: usize = 4;
+
+/// How long to wait for the global epoch to advance.
+const EPOCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Advance the global epoch of scc until the entries retired by this thread
+/// are freed.
+///
+/// sdd, the collector of scc, does not tell whether garbage is pending, and
+/// the entries retired by exited threads are freed by whichever thread scans
+/// them next, so reclamation is flagged as best-effort.
+fn flush_scc_ebr() {
+    crate::reclaim::mark_best_effort();
+
+    let deadline = Instant::now() + EPOCH_TIMEOUT;
+    let mut epoch = Guard::new().epoch();
+    let mut witnessed = 0;
+
+    while witnessed < EPOCHS_TO_RECLAIM && Instant::now() < deadline {
+        Guard::new().accelerate();
+
+        match Guard::new().epoch() {
+            current if current != epoch => {
+                epoch = current;
+                witnessed += 1;
+            }
+            _ => thread::yield_now(),
+        }
+    }
+}
+
 table!(SccMap, Value, <K, H>);
 
 impl_collection! {
@@ -9,6 +46,10 @@ impl_collection! {
     with_capacity |capacity| {
         SccMap::with_capacity_and_hasher(capacity, H::default())
     };
+    reclaim |self| {
+        drop(self);
+        flush_scc_ebr();
+    };
     get |self, key|  {
         self.0.read(key, |_, v| *v).is_some()
     };
@@ -36,6 +77,10 @@ impl_collection! {
     with_capacity |capacity| {
         SccIndex::with_capacity_and_hasher(capacity, H::default())
     };
+    reclaim |self| {
+        drop(self);
+        flush_scc_ebr();
+    };
     get |self, key|  {
         self.0.peek_with(key, |_, v| *v).is_some()
     };
@@ -55,3 +100,29 @@ impl_collection! {
         }
     }
 }
+
+table!(SccCache, Value, <K, H>);
+
+impl_collection! {
+    |K, H| SccCacheTable<K, H>;
+    with_capacity |capacity| {
+        // Evicting once full rather than growing.
+        SccCache::with_capacity_and_hasher(capacity, capacity, H::default())
+    };
+    reclaim |self| {
+        drop(self);
+        flush_scc_ebr();
+    };
+    get |self, key|  {
+        self.0.get(key).is_some()
+    };
+    insert |self, key| {
+        self.0.put(*key, 0).is_ok()
+    };
+    remove |self, key| {
+        self.0.remove(key).is_some()
+    };
+    update |self, key| {
+        self.0.get(key).map(|mut v| *v.get_mut() += 1).is_some()
+    }
+}
@@ -0,0 +1,219 @@
+//! N independently locked std `HashMap`s, the simplest sharded map.
+//!
+//! This is the reference between a single locked `HashMap` and the sharded
+//! maps of third party crates. bustle creates tables from their capacity
+//! alone, so the shard count is a parameter of the table type.
+
+use std::{collections::HashMap as StdHashMap, marker::PhantomData};
+
+use super::{prelude::*, HasherT, KeyT, Reclaim};
+
+/// The lock guarding each shard.
+pub trait ShardLock: 'static {
+    type Lock<T: Send + Sync>: Send + Sync;
+
+    fn new<T: Send + Sync>(value: T) -> Self::Lock<T>;
+
+    fn read<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&T) -> R) -> R;
+
+    fn write<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&mut T) -> R) -> R;
+}
+
+pub struct StdRwLockShard;
+
+impl ShardLock for StdRwLockShard {
+    type Lock<T: Send + Sync> = StdRwLock<T>;
+
+    fn new<T: Send + Sync>(value: T) -> Self::Lock<T> {
+        StdRwLock::new(value)
+    }
+
+    fn read<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&T) -> R) -> R {
+        f(&lock.read().unwrap())
+    }
+
+    fn write<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut lock.write().unwrap())
+    }
+}
+
+pub struct ParkingLotRwLockShard;
+
+impl ShardLock for ParkingLotRwLockShard {
+    type Lock<T: Send + Sync> = ParkingLotRwLock<T>;
+
+    fn new<T: Send + Sync>(value: T) -> Self::Lock<T> {
+        ParkingLotRwLock::new(value)
+    }
+
+    fn read<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&T) -> R) -> R {
+        f(&lock.read())
+    }
+
+    fn write<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut lock.write())
+    }
+}
+
+/// Reads take the lock exclusively too.
+pub struct StdMutexShard;
+
+impl ShardLock for StdMutexShard {
+    type Lock<T: Send + Sync> = StdMutex<T>;
+
+    fn new<T: Send + Sync>(value: T) -> Self::Lock<T> {
+        StdMutex::new(value)
+    }
+
+    fn read<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&T) -> R) -> R {
+        f(&lock.lock().unwrap())
+    }
+
+    fn write<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut lock.lock().unwrap())
+    }
+}
+
+/// Reads take the lock exclusively too.
+pub struct ParkingLotMutexShard;
+
+impl ShardLock for ParkingLotMutexShard {
+    type Lock<T: Send + Sync> = ParkingLotMutex<T>;
+
+    fn new<T: Send + Sync>(value: T) -> Self::Lock<T> {
+        ParkingLotMutex::new(value)
+    }
+
+    fn read<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&T) -> R) -> R {
+        f(&lock.lock())
+    }
+
+    fn write<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut lock.lock())
+    }
+}
+
+/// Puts every shard on cache lines of its own, so that locking one does not
+/// invalidate its neighbours in other cores.
+pub struct Padded<L>(PhantomData<L>);
+
+/// Two cache lines, as the adjacent line prefetcher of x86 pulls lines in
+/// pairs.
+#[repr(align(128))]
+pub struct CachePadded<T>(T);
+
+impl<L: ShardLock> ShardLock for Padded<L> {
+    type Lock<T: Send + Sync> = CachePadded<L::Lock<T>>;
+
+    fn new<T: Send + Sync>(value: T) -> Self::Lock<T> {
+        CachePadded(L::new(value))
+    }
+
+    fn read<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&T) -> R) -> R {
+        L::read(&lock.0, f)
+    }
+
+    fn write<T: Send + Sync, R>(lock: &Self::Lock<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        L::write(&lock.0, f)
+    }
+}
+
+type Shard<K, H> = StdHashMap<K, Value, H>;
+
+pub struct Shards<K: KeyT, H: HasherT, L: ShardLock> {
+    hasher: H,
+    shift: u32,
+    shards: Box<[L::Lock<Shard<K, H>>]>,
+}
+
+impl<K: KeyT, H: HasherT, L: ShardLock> Shards<K, H, L> {
+    #[inline]
+    fn shard(&self, key: &K) -> &L::Lock<Shard<K, H>> {
+        // Like dashmap, skip the top 7 bits which hashbrown keeps in its
+        // control bytes, and the bottom ones it picks buckets with.
+        let hash = self.hasher.hash_one(key) << 7;
+        &self.shards[hash.checked_shr(self.shift).unwrap_or(0) as usize]
+    }
+}
+
+/// `SHARDS` std `HashMap`s, a power of two.
+pub struct ShardedTable<K: KeyT, H: HasherT, L: ShardLock, const SHARDS: usize>(
+    Arc<Shards<K, H, L>>,
+);
+
+impl<K: KeyT, H: HasherT, L: ShardLock, const SHARDS: usize> Clone
+    for ShardedTable<K, H, L, SHARDS>
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K: KeyT, H: HasherT, L: ShardLock, const SHARDS: usize> bustle::Collection
+    for ShardedTable<K, H, L, SHARDS>
+{
+    type Handle = Self;
+
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        const {
+            assert!(
+                SHARDS.is_power_of_two(),
+                "shard count must be a power of two"
+            )
+        };
+
+        let hasher = H::default();
+
+        Self(Arc::new(Shards {
+            shift: u64::BITS - SHARDS.trailing_zeros(),
+            shards: (0..SHARDS)
+                .map(|_| {
+                    L::new(Shard::with_capacity_and_hasher(
+                        capacity.div_ceil(SHARDS),
+                        hasher.clone(),
+                    ))
+                })
+                .collect(),
+            hasher,
+        }))
+    }
+
+    #[inline]
+    fn pin(&self) -> Self::Handle {
+        self.clone()
+    }
+}
+
+impl<K: KeyT, H: HasherT, L: ShardLock, const SHARDS: usize> bustle::CollectionHandle
+    for ShardedTable<K, H, L, SHARDS>
+{
+    type Key = K;
+
+    #[inline]
+    fn get(&mut self, key: &Self::Key) -> bool {
+        L::read(self.0.shard(key), |map| map.get(key).is_some())
+    }
+
+    #[inline]
+    fn insert(&mut self, key: &Self::Key) -> bool {
+        L::write(self.0.shard(key), |map| map.insert(*key, 0).is_none())
+    }
+
+    #[inline]
+    fn remove(&mut self, key: &Self::Key) -> bool {
+        L::write(self.0.shard(key), |map| map.remove(key).is_some())
+    }
+
+    #[inline]
+    fn update(&mut self, key: &Self::Key) -> bool {
+        L::write(self.0.shard(key), |map| {
+            map.get_mut(key).map(|v| *v += 1).is_some()
+        })
+    }
+}
+
+impl<K: KeyT, H: HasherT, L: ShardLock, const SHARDS: usize> Reclaim
+    for ShardedTable<K, H, L, SHARDS>
+{
+}
@@ -55,3 +55,82 @@ impl_collection! {
         map.get_mut(key).map(|v| *v += 1).is_some()
     }
 }
+
+table!(StdMutex, StdHashMap, Value, <K, H>);
+
+impl_collection! {
+    |K, H| StdMutexStdHashMapTable<K, H>;
+    with_capacity |capacity| {
+        StdMutex::new(StdHashMap::with_capacity_and_hasher(
+            capacity,
+            H::default(),
+        ))
+    };
+    get |self, key|  {
+        self.0.lock().unwrap().get(key).is_some()
+    };
+    insert |self, key| {
+        self.0.lock().unwrap().insert(*key, 0).is_none()
+    };
+    remove |self, key| {
+        self.0.lock().unwrap().remove(key).is_some()
+    };
+    update |self, key| {
+        self.0
+            .lock()
+            .unwrap()
+            .get_mut(key)
+            .map(|v| *v += 1)
+            .is_some()
+    }
+}
+
+table!(ParkingLotMutex, StdHashMap, Value, <K, H>);
+
+impl_collection! {
+    |K, H| ParkingLotMutexStdHashMapTable<K, H>;
+    with_capacity |capacity| {
+        ParkingLotMutex::new(StdHashMap::with_capacity_and_hasher(
+            capacity,
+            H::default(),
+        ))
+    };
+    get |self, key|  {
+        self.0.lock().get(key).is_some()
+    };
+    insert |self, key| {
+        self.0.lock().insert(*key, 0).is_none()
+    };
+    remove |self, key| {
+        self.0.lock().remove(key).is_some()
+    };
+    update |self, key| {
+        let mut map = self.0.lock();
+        map.get_mut(key).map(|v| *v += 1).is_some()
+    }
+}
+
+table!(SpinLock, StdHashMap, Value, <K, H>);
+
+impl_collection! {
+    |K, H| SpinLockStdHashMapTable<K, H>;
+    with_capacity |capacity| {
+        SpinLock::new(StdHashMap::with_capacity_and_hasher(
+            capacity,
+            H::default(),
+        ))
+    };
+    get |self, key|  {
+        self.0.lock().get(key).is_some()
+    };
+    insert |self, key| {
+        self.0.lock().insert(*key, 0).is_none()
+    };
+    remove |self, key| {
+        self.0.lock().remove(key).is_some()
+    };
+    update |self, key| {
+        let mut map = self.0.lock();
+        map.get_mut(key).map(|v| *v += 1).is_some()
+    }
+}
@@ -0,0 +1,351 @@
+//! Worker thread placement.
+//!
+//! bustle spawns its own threads, but every thread pins a handle of the
+//! collection before doing any work. [`Pinned`] hooks into that to bind the
+//! current thread to a CPU chosen by the armed [`PinPolicy`].
+//!
+//! Only the CPUs the process is allowed to run on are handed out, so that a
+//! cpuset restricted container gets a placement within its own CPUs.
+
+use std::{
+    fs, io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, LazyLock, Mutex,
+    },
+};
+
+use crate::args::PinPolicy;
+
+const SYSFS_CPU: &str = "/sys/devices/system/cpu";
+
+/// The number of CPUs a `cpu_set_t` can hold, the bound of CPU ids.
+#[cfg(target_os = "linux")]
+pub(crate) const CPU_SETSIZE: usize = libc::CPU_SETSIZE as usize;
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) const CPU_SETSIZE: usize = 1024;
+
+#[derive(Debug, Clone, Copy)]
+struct Cpu {
+    id: usize,
+    package: usize,
+    core: usize,
+    /// The index of this CPU among the SMT siblings of its physical core.
+    smt: usize,
+}
+
+/// Parse a CPU list in the kernel format, e.g. `0-3,8,10-11`.
+///
+/// Returns `None` if the list is malformed or names a CPU beyond
+/// [`CPU_SETSIZE`].
+pub(crate) fn parse_cpu_list(s: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    let parse = |id: &str| id.parse::<usize>().ok().filter(|&id| id < CPU_SETSIZE);
+
+    for part in s.trim().split(',').filter(|part| !part.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => cpus.extend(parse(start)?..=parse(end)?),
+            None => cpus.push(parse(part)?),
+        }
+    }
+
+    Some(cpus)
+}
+
+/// Format CPUs in the kernel list format, collapsing consecutive ids into
+/// ranges.
+pub(crate) fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut cpus = cpus.to_vec();
+    cpus.sort_unstable();
+    cpus.dedup();
+
+    let mut ranges = Vec::<(usize, usize)>::new();
+
+    for cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| match start == end {
+            true => start.to_string(),
+            false => format!("{start}-{end}"),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The CPUs the process is allowed to run on.
+#[cfg(target_os = "linux")]
+pub(crate) fn allowed_cpus() -> io::Result<Vec<usize>> {
+    // SAFETY: `set` is a plain bit mask owned by this frame, and pid 0 refers
+    // to the calling thread, which is never pinned.
+    unsafe {
+        let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+
+        if libc::sched_getaffinity(0, size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok((0..CPU_SETSIZE)
+            .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+            .collect())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn allowed_cpus() -> io::Result<Vec<usize>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reading the CPU affinity is only supported on Linux",
+    ))
+}
+
+/// Read the online CPUs the process is allowed to run on and their topology
+/// from sysfs.
+fn topology() -> io::Result<Vec<Cpu>> {
+    let read_id = |cpu: usize, name: &str| -> io::Result<usize> {
+        fs::read_to_string(format!("{SYSFS_CPU}/cpu{cpu}/topology/{name}"))?
+            .trim()
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    };
+
+    let online = fs::read_to_string(format!("{SYSFS_CPU}/online"))?;
+    let online = parse_cpu_list(&online)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid online CPU list"))?;
+    let allowed = allowed_cpus()?;
+
+    let mut cpus = online
+        .into_iter()
+        .filter(|id| allowed.contains(id))
+        .map(|id| {
+            Ok(Cpu {
+                id,
+                package: read_id(id, "physical_package_id")?,
+                core: read_id(id, "core_id")?,
+                smt: 0,
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    cpus.sort_by_key(|cpu| cpu.id);
+
+    for i in 0..cpus.len() {
+        cpus[i].smt = cpus[..i]
+            .iter()
+            .filter(|cpu| cpu.package == cpus[i].package && cpu.core == cpus[i].core)
+            .count();
+    }
+
+    Ok(cpus)
+}
+
+/// The number of CPUs and of physical cores the process is allowed to run
+/// on, regardless of any cgroup CPU quota.
+pub(crate) fn allowed_cores() -> io::Result<(usize, usize)> {
+    let cpus = topology()?;
+
+    Ok((cpus.len(), cpus.iter().filter(|cpu| cpu.smt == 0).count()))
+}
+
+/// The CPUs to pin worker threads to, in the order they are handed out.
+fn placement(policy: &PinPolicy) -> io::Result<Vec<usize>> {
+    let mut cpus = match policy {
+        PinPolicy::None => return Ok(Vec::new()),
+        PinPolicy::List(list) => {
+            let allowed = allowed_cpus()?;
+
+            return match list.iter().find(|cpu| !allowed.contains(cpu)) {
+                Some(cpu) => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "CPU {cpu} is not allowed, the process may only run on CPUs {}",
+                        format_cpu_list(&allowed)
+                    ),
+                )),
+                None => Ok(list.clone()),
+            };
+        }
+        PinPolicy::Compact | PinPolicy::Scatter => topology()?,
+    };
+
+    if cpus.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no online CPU is allowed for the process",
+        ));
+    }
+
+    match policy {
+        // Fill the physical cores of one package, then their SMT siblings,
+        // before moving on to the next package.
+        PinPolicy::Compact => cpus.sort_by_key(|cpu| (cpu.package, cpu.smt, cpu.core, cpu.id)),
+        // Round-robin over the packages, using every physical core before any
+        // SMT sibling.
+        _ => {
+            let mut rank = vec![0; cpus.len()];
+
+            for (i, cpu) in cpus.iter().enumerate() {
+                rank[i] = cpus
+                    .iter()
+                    .filter(|other| {
+                        other.package == cpu.package
+                            && other.smt == cpu.smt
+                            && (other.core, other.id) < (cpu.core, cpu.id)
+                    })
+                    .count();
+            }
+
+            let mut ranked = cpus.into_iter().zip(rank).collect::<Vec<_>>();
+            ranked.sort_by_key(|(cpu, rank)| (cpu.smt, *rank, cpu.package));
+            cpus = ranked.into_iter().map(|(cpu, _)| cpu).collect();
+        }
+    }
+
+    Ok(cpus.into_iter().map(|cpu| cpu.id).collect())
+}
+
+#[derive(Debug, Default)]
+struct Placement {
+    cpus: Vec<usize>,
+    threads: usize,
+    next: AtomicUsize,
+}
+
+static PLACEMENT: LazyLock<Mutex<Arc<Placement>>> = LazyLock::new(Default::default);
+
+/// The CPUs the worker threads of the armed run are pinned to, none if they
+/// are left to the OS.
+pub(crate) fn armed_cpus() -> Option<Vec<usize>> {
+    let placement = PLACEMENT.lock().unwrap().clone();
+
+    (!placement.cpus.is_empty()).then(|| {
+        (0..placement.threads)
+            .map(|slot| placement.cpus[slot % placement.cpus.len()])
+            .collect()
+    })
+}
+
+/// Prepare the placement of the `threads` worker threads of the next run.
+pub(crate) fn arm(policy: &PinPolicy, threads: u32) -> io::Result<()> {
+    let cpus = placement(policy)?;
+
+    if !cpus.is_empty() && cfg!(not(target_os = "linux")) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "pinning threads is only supported on Linux",
+        ));
+    }
+
+    *PLACEMENT.lock().unwrap() = Arc::new(Placement {
+        cpus,
+        threads: threads as usize,
+        next: AtomicUsize::new(0),
+    });
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn pin_current_thread(cpu: usize) -> io::Result<()> {
+    // SAFETY: `set` is a plain bit mask owned by this frame, and pid 0 refers
+    // to the calling thread.
+    unsafe {
+        let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+        libc::CPU_SET(cpu, &mut set);
+
+        if libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_current_thread(_cpu: usize) -> io::Result<()> {
+    unreachable!("rejected by `arm`")
+}
+
+/// A collection that pins the thread calling [`bustle::Collection::pin`] to
+/// the next CPU of the armed placement.
+pub(crate) struct Pinned<C> {
+    inner: C,
+    placement: Arc<Placement>,
+}
+
+impl<C> bustle::Collection for Pinned<C>
+where
+    C: bustle::Collection,
+{
+    type Handle = C::Handle;
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: C::with_capacity(capacity),
+            placement: PLACEMENT.lock().unwrap().clone(),
+        }
+    }
+
+    fn pin(&self) -> Self::Handle {
+        let placement = &self.placement;
+
+        if !placement.cpus.is_empty() {
+            // Prefill and worker threads each pin exactly one handle per
+            // thread, so both phases get the same set of CPUs.
+            let slot = placement.next.fetch_add(1, Ordering::Relaxed) % placement.threads;
+            let cpu = placement.cpus[slot % placement.cpus.len()];
+
+            pin_current_thread(cpu)
+                .unwrap_or_else(|e| panic!("cannot pin thread to CPU {cpu}: {e}"));
+        }
+
+        self.inner.pin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_kernel_cpu_lists() {
+        assert_eq!(
+            parse_cpu_list("0-3,8,10-11\n"),
+            Some(vec![0, 1, 2, 3, 8, 10, 11])
+        );
+        assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("3-1"), Some(vec![]));
+    }
+
+    #[test]
+    fn rejects_malformed_cpu_lists() {
+        assert_eq!(parse_cpu_list("a"), None);
+        assert_eq!(parse_cpu_list("0-"), None);
+        assert_eq!(parse_cpu_list("-3"), None);
+        assert_eq!(parse_cpu_list("1,,x"), None);
+    }
+
+    #[test]
+    fn rejects_cpus_beyond_the_cpu_set() {
+        assert_eq!(
+            parse_cpu_list(&(CPU_SETSIZE - 1).to_string()),
+            Some(vec![CPU_SETSIZE - 1])
+        );
+        assert_eq!(parse_cpu_list(&CPU_SETSIZE.to_string()), None);
+        assert_eq!(parse_cpu_list(&format!("0-{}", usize::MAX)), None);
+    }
+
+    #[test]
+    fn formats_kernel_cpu_lists() {
+        assert_eq!(format_cpu_list(&[11, 0, 1, 2, 3, 8, 10, 2]), "0-3,8,10-11");
+        assert_eq!(format_cpu_list(&[4]), "4");
+        assert_eq!(format_cpu_list(&[]), "");
+    }
+}
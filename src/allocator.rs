@@ -0,0 +1,214 @@
+//! The global allocator, picked at startup among those compiled in.
+//!
+//! Every allocator enabled by the `alloc_*` features is linked in, and
+//! [`Dispatch`] forwards to the one selected with `--allocator` or the
+//! `CONC_MAP_BENCH_ALLOCATOR` environment variable. Memory has to be freed by
+//! the allocator it came from, so the choice is made before the first
+//! allocation and never changes: on Linux with glibc the command line is
+//! scanned before `main`, elsewhere only the environment is read early enough.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    ffi::CStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use crate::args::Allocator;
+
+/// The environment variable selecting the allocator.
+pub(crate) const ENV: &str = "CONC_MAP_BENCH_ALLOCATOR";
+
+const ENV_C: &CStr = c"CONC_MAP_BENCH_ALLOCATOR";
+
+const UNSET: u8 = u8::MAX;
+
+/// The index of the selected allocator in [`Allocator::ALL`].
+static SELECTED: AtomicU8 = AtomicU8::new(UNSET);
+
+/// Forwards every allocation to the selected allocator.
+pub(crate) struct Dispatch;
+
+macro_rules! dispatch {
+    ($method:ident($($arg:expr),*)) => {
+        match current() {
+            #[cfg(feature = "alloc_mimalloc")]
+            Allocator::Mimalloc => mimalloc::MiMalloc.$method($($arg),*),
+            #[cfg(feature = "alloc_jemalloc")]
+            Allocator::Jemalloc => tikv_jemallocator::Jemalloc.$method($($arg),*),
+            _ => System.$method($($arg),*),
+        }
+    };
+}
+
+unsafe impl GlobalAlloc for Dispatch {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        dispatch!(alloc(layout))
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        dispatch!(dealloc(ptr, layout))
+    }
+
+    #[inline]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        dispatch!(alloc_zeroed(layout))
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        dispatch!(realloc(ptr, layout, new_size))
+    }
+}
+
+/// The allocator in use, selected from the environment on first use.
+#[inline]
+pub(crate) fn current() -> Allocator {
+    match SELECTED.load(Ordering::Relaxed) {
+        UNSET => select(from_env().unwrap_or_else(default)),
+        i => from_index(i),
+    }
+}
+
+/// Check that the allocator asked for on the command line or in the
+/// environment is the one in use, and return it.
+pub(crate) fn check(requested: Option<Allocator>) -> Result<Allocator, String> {
+    let requested = match requested {
+        Some(allocator) => Some(allocator),
+        None => match env::var(ENV) {
+            Ok(name) => Some(name.parse().map_err(|err| format!("{}: {}", ENV, err))?),
+            Err(_) => None,
+        },
+    };
+
+    let current = current();
+
+    match requested {
+        Some(allocator) if !allocator.is_available() => Err(format!(
+            "{} is not compiled in, enable the 'alloc_{}' feature",
+            allocator, allocator
+        )),
+        Some(allocator) if allocator != current => Err(format!(
+            "{} was selected before the command line was read, set {}={} instead",
+            current, ENV, allocator
+        )),
+        _ => Ok(current),
+    }
+}
+
+/// Use `allocator` unless one is in use already, and return the one in use.
+fn select(allocator: Allocator) -> Allocator {
+    match SELECTED.compare_exchange(
+        UNSET,
+        index(allocator),
+        Ordering::Relaxed,
+        Ordering::Relaxed,
+    ) {
+        Ok(_) => allocator,
+        Err(i) => from_index(i),
+    }
+}
+
+/// The index of `allocator` in [`Allocator::ALL`], whatever the order of its
+/// variants.
+fn index(allocator: Allocator) -> u8 {
+    Allocator::ALL
+        .iter()
+        .position(|listed| *listed == allocator)
+        .unwrap_or_else(|| unreachable!("{:?} is not listed", allocator)) as u8
+}
+
+fn from_index(i: u8) -> Allocator {
+    Allocator::ALL[usize::from(i)]
+}
+
+/// The first compiled in allocator, system last.
+fn default() -> Allocator {
+    Allocator::ALL
+        .into_iter()
+        .find(|allocator| allocator.is_available())
+        .unwrap_or(Allocator::System)
+}
+
+/// A compiled in allocator by name, without allocating.
+fn from_name(name: &[u8]) -> Option<Allocator> {
+    Allocator::ALL.into_iter().find(|allocator| {
+        allocator.is_available() && allocator.name().as_bytes().eq_ignore_ascii_case(name)
+    })
+}
+
+fn from_env() -> Option<Allocator> {
+    // SAFETY: the name is a valid C string, and the value is only read before
+    // being returned.
+    unsafe {
+        let value = libc::getenv(ENV_C.as_ptr());
+        (!value.is_null())
+            .then(|| CStr::from_ptr(value).to_bytes())
+            .and_then(from_name)
+    }
+}
+
+/// Select the allocator given on the command line, called by glibc with the
+/// arguments of the process before `main`.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[used]
+#[link_section = ".init_array"]
+static SELECT_FROM_ARGS: extern "C" fn(
+    libc::c_int,
+    *const *const libc::c_char,
+    *const *const libc::c_char,
+) = {
+    extern "C" fn select_from_args(
+        argc: libc::c_int,
+        argv: *const *const libc::c_char,
+        _envp: *const *const libc::c_char,
+    ) {
+        // SAFETY: glibc passes the `argc` arguments of the process as C
+        // strings living as long as it.
+        let mut args = (0..argc.max(0) as usize)
+            .map(|i| unsafe { CStr::from_ptr(*argv.add(i)) }.to_bytes())
+            .take_while(|&arg| arg != b"--");
+
+        while let Some(arg) = args.next() {
+            let name = match arg.strip_prefix(b"--allocator") {
+                Some(b"") => args.next(),
+                Some(rest) => rest.strip_prefix(b"="),
+                None => continue,
+            };
+
+            // Invalid names are reported once the command line is parsed.
+            if let Some(allocator) = name.and_then(from_name) {
+                select(allocator);
+            }
+
+            break;
+        }
+    }
+
+    select_from_args
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_round_trip() {
+        for allocator in [Allocator::Mimalloc, Allocator::Jemalloc, Allocator::System] {
+            assert_ne!(index(allocator), UNSET);
+            assert_eq!(from_index(index(allocator)), allocator);
+        }
+    }
+
+    #[test]
+    fn names_of_compiled_in_allocators() {
+        assert_eq!(from_name(b"SYSTEM"), Some(Allocator::System));
+        assert_eq!(
+            from_name(b"mimalloc"),
+            cfg!(feature = "alloc_mimalloc").then_some(Allocator::Mimalloc)
+        );
+        assert_eq!(from_name(b"tcmalloc"), None);
+    }
+}
@@ -1,8 +1,9 @@
 //! Args definition
 
-use std::{path::PathBuf, str::FromStr, sync::Arc};
+use std::{fmt, fmt::Debug, path::PathBuf, str::FromStr, sync::Arc};
 
 use clap::{Parser, Subcommand};
+use regex::Regex;
 
 #[derive(Debug, Parser)]
 pub(crate) struct Args {
@@ -16,6 +17,9 @@ pub(crate) enum Command {
     Bench {
         #[arg(short('w'), long)]
         /// Select work load type.
+        ///
+        /// Must be one of 'ReadHeavy', 'Exchange', 'RapidGrow' or 'Cache', the
+        /// latter running the caches instead of the maps.
         workload: WorkloadType,
 
         #[arg(short, long, default_value = "1")]
@@ -26,6 +30,20 @@ pub(crate) enum Command {
         /// Set the number of threads to use.
         threads: Option<Vec<u32>>,
 
+        #[arg(long, conflicts_with = "threads")]
+        /// Sweep the number of threads over an inclusive range, e.g. '1..16:2'.
+        ///
+        /// The step defaults to 1.
+        threads_range: Option<ThreadsRange>,
+
+        #[arg(long, default_value = "linear")]
+        /// Set how the number of threads is swept when not given explicitly.
+        ///
+        /// Must be one of 'linear', 'pow2', 'physical-cores' or
+        /// 'oversubscribe:<factor>'. The number of available CPUs respects the
+        /// affinity mask and cgroup CPU quota of the process.
+        threads_policy: ThreadsPolicy,
+
         #[arg(short, long)]
         /// Set the hasher to use.
         ///
@@ -33,10 +51,65 @@ pub(crate) enum Command {
         hasher: HasherKind,
 
         #[arg(long, default_value = "2000")]
-        /// Set the number of milliseconds to sleep between GC cycles.
+        /// Set the maximum number of milliseconds to wait between runs for the
+        /// memory usage to settle after the table has been reclaimed.
         gc_sleep_ms: u64,
 
-        #[arg(long, default_value = "crossbeam_skiplist,chashmap,evmap", value_delimiter = ',')]
+        #[arg(long, default_value = "none")]
+        /// Set how worker threads are pinned to CPUs.
+        ///
+        /// Must be one of 'none', 'compact' (fill the physical cores of one
+        /// package, then their SMT siblings, then the next package), 'scatter'
+        /// (round-robin over packages, physical cores before SMT siblings) or
+        /// 'list:<cpus>' (e.g. 'list:0-3,8').
+        pin: PinPolicy,
+
+        #[arg(long, value_delimiter = ',')]
+        /// Add in-tree sharded map cases with the given shard counts, each a
+        /// power of four up to 1024 and a bench case of its own, e.g. '4,16,64'.
+        shards: Vec<usize>,
+
+        #[arg(long)]
+        /// Pad every shard of the in-tree sharded maps to cache lines of its
+        /// own.
+        shard_padding: bool,
+
+        #[arg(long, value_delimiter = ',')]
+        /// Add dashmap cases with a fixed shard amount, each a power of four
+        /// from 4 to 1024 and a bench case of its own, e.g. '4,16,64,256'.
+        ///
+        /// The default cases leave the shard amount to dashmap, which derives
+        /// it from the CPU count.
+        dashmap_shards: Vec<usize>,
+
+        #[arg(long, value_delimiter = ',')]
+        /// Add papaya and flurry cases whose handles pin a guard once and
+        /// refresh it every given number of operations, each a power of four up
+        /// to 1024 and a bench case of its own, e.g. '1,64,1024'.
+        ///
+        /// The default cases pin on every operation. Longer intervals pin less
+        /// often but delay reclamation.
+        guard_refresh: Vec<usize>,
+
+        #[arg(long)]
+        /// Set the global allocator, overriding the `CONC_MAP_BENCH_ALLOCATOR`
+        /// environment variable.
+        ///
+        /// Must be one of 'mimalloc', 'jemalloc' or 'system', among those
+        /// compiled in with the `alloc_*` features. Defaults to the first one
+        /// compiled in.
+        allocator: Option<Allocator>,
+
+        #[arg(long)]
+        /// Record the fairness of every run, counting the operations of each
+        /// worker thread, at the cost of a counter per operation.
+        fairness: bool,
+
+        #[arg(
+            long,
+            default_value = "crossbeam_skiplist,chashmap,evmap",
+            value_delimiter = ','
+        )]
         /// Skip the given cases.
         ///
         /// Since the following crates are with much worser perf, they are skipped by default:
@@ -60,17 +133,77 @@ pub(crate) enum Command {
         /// Set the directory to export the plots to.
         ///
         /// The plots will be exported as:
-        /// <dir>/<name>.throughput.svg
-        /// <dir>/<name>.latency.svg
+        /// <dir>/<name>.throughput.<format>
+        /// <dir>/<name>.latency.<format>
+        /// <dir>/<name>.fairness.<format> (if the results carry fairness metrics)
+        /// <dir>/<name>.speedup.<format>
+        /// <dir>/<name>.efficiency.<format>
+        /// <dir>/<name>.bars.<format>
+        /// <dir>/<name>.heatmap.<format>
+        ///
+        /// for each requested kind. With a baseline, the throughput and latency
+        /// plots are exported as <dir>/<name>.relative-<kind>.<format>.
+        ///
+        /// Without a name nor inputs, every '*.csv' of the directory is read
+        /// and its plots are regenerated in place.
         dir: PathBuf,
 
-        /// Set the name of the plot.
-        name: String,
+        /// Set the name of the plot, and read the results from stdin.
+        ///
+        /// With inputs, each input is plotted under its file stem instead and
+        /// the name is only used by the charts summarizing all of them. It
+        /// defaults to the name of the directory.
+        name: Option<String>,
+
+        #[arg(short, long)]
+        /// Read results from the given CSV files, or from every '*.csv' of the
+        /// given directories, instead of stdin.
+        ///
+        /// Files are expected to be named '<workload>.<hasher>.csv'.
+        input: Vec<PathBuf>,
+
+        #[arg(long)]
+        /// Merge every input into a single set of plots under the given name,
+        /// telling their series apart by the input they come from.
+        overlay: bool,
 
         #[arg(short, long, default_value = "640")]
         /// Set the width of the plot.
         width: u32,
 
+        #[arg(long, conflicts_with_all = ["overlay", "group_by"])]
+        /// Compare allocators, reading each input as the results of one
+        /// allocator, e.g. 'results/mimalloc results/jemalloc'.
+        ///
+        /// Every workload and hasher is plotted once, with a series per map
+        /// and allocator, allocators being told apart by their dash pattern.
+        /// Results that don't record their allocator are assumed to come from
+        /// the one named after their directory.
+        by_allocator: bool,
+
+        #[arg(long)]
+        /// Only plot the series whose name matches any of the given regexes,
+        /// e.g. '^dashmap'.
+        include: Vec<Regex>,
+
+        #[arg(long)]
+        /// Drop the series whose name matches any of the given regexes.
+        exclude: Vec<Regex>,
+
+        #[arg(long)]
+        /// Rename a series, or every series of a crate keeping their
+        /// versions, e.g. 'dashmap5=dashmap'.
+        ///
+        /// Series are renamed after being filtered.
+        rename: Vec<Rename>,
+
+        #[arg(long, default_value = "source")]
+        /// Plot the inputs one by one, or gather them by workload, hasher or
+        /// map family, labelling each series with what tells it apart.
+        ///
+        /// Must be one of 'source', 'family', 'hasher' or 'workload'.
+        group_by: GroupBy,
+
         #[arg(short, long, default_value = "480")]
         /// Set the height of the plot.
         height: u32,
@@ -78,9 +211,178 @@ pub(crate) enum Command {
         #[arg(long, default_value = "2000")]
         /// Set the latency limit in nanoseconds.
         latency_limit_ns: u64,
+
+        #[arg(long, default_value = "limit")]
+        /// Set how the latency axis is bounded.
+        ///
+        /// Either 'limit' to cut it at the latency limit, 'p<percentile>' to
+        /// fit a percentile of every latency, e.g. 'p90', or 'log' for a log
+        /// scale fitting them all. Series reaching past the axis are marked
+        /// where they leave it and listed with their highest latency.
+        latency_axis: LatencyAxis,
+
+        #[arg(short, long, default_value = "svg", value_delimiter = ',')]
+        /// Set the image formats to export, e.g. 'svg,png'.
+        ///
+        /// Must be one of 'svg' or 'png'.
+        format: Vec<ImageFormat>,
+
+        #[arg(
+            short,
+            long,
+            default_value = "throughput,latency,fairness",
+            value_delimiter = ','
+        )]
+        /// Set the kinds of plots to export, e.g. 'throughput,speedup'.
+        ///
+        /// Must be one of 'throughput', 'latency', 'fairness', 'speedup',
+        /// 'efficiency', 'bars' or 'heatmap'.
+        kind: Vec<PlotKind>,
+
+        #[arg(long)]
+        /// Use a log2 scale for the threads axis of the speedup and
+        /// efficiency plots.
+        log2_threads: bool,
+
+        #[arg(short, long)]
+        /// Plot throughput and latency as ratios to the given series.
+        ///
+        /// Matches the series name exactly, or as the only series starting with
+        /// it, e.g. 'std(parking_lot)'. With --overlay, every source is compared
+        /// to its own baseline.
+        baseline: Option<String>,
+
+        #[arg(long, default_value = "max")]
+        /// Set the thread count the bar chart and the heatmap compare the maps
+        /// at.
+        ///
+        /// Either a number of threads, or 'max' for the most threads each map
+        /// ran with.
+        at_threads: AtThreads,
+
+        #[arg(long, default_value = "source")]
+        /// Group the bars by the input they come from, or by its workload or
+        /// hasher.
+        ///
+        /// Must be one of 'source', 'workload' or 'hasher'.
+        bars_by: BarGroup,
+
+        #[arg(long, default_value = "workload")]
+        /// Set the columns of the heatmap, one per input or one per thread
+        /// count.
+        ///
+        /// Must be one of 'workload' or 'threads'.
+        heatmap_columns: HeatmapColumns,
+
+        #[arg(long, default_value = "column")]
+        /// Color the heatmap cells relative to the best cell of their row or
+        /// of their column.
+        ///
+        /// Must be one of 'row' or 'column'.
+        heatmap_relative: HeatmapRelative,
+
+        #[arg(long)]
+        /// Plot the throughput and latency of every input into a single
+        /// <dir>/<name>.dashboard.<format> instead.
+        ///
+        /// Rows are workloads and columns are hashers and metrics, each panel
+        /// of the given width and height.
+        dashboard: bool,
+
+        #[arg(long)]
+        /// Read the colors, markers and dash patterns of the series from the
+        /// given TOML file.
+        ///
+        /// Series missing from it are styled from a hash of their map family.
+        /// See the `palette` command to generate one.
+        theme: Option<PathBuf>,
+
+        #[arg(long, num_args = 0..=1, default_missing_value = "braille")]
+        /// Print the throughput and latency charts and a table of the peak
+        /// throughput of every map to the terminal instead of exporting images.
+        ///
+        /// Charts are drawn with 'braille' dots by default, or with 'block'
+        /// characters.
+        terminal: Option<TerminalChars>,
+    },
+
+    /// Write an interactive HTML report of results
+    Report {
+        #[arg(required = true)]
+        /// Read results from the given CSV files, or from every '*.csv' of the
+        /// given directories.
+        ///
+        /// Files are expected to be named '<workload>.<hasher>.csv'.
+        input: Vec<PathBuf>,
+
+        #[arg(short, long, default_value = "report.html")]
+        /// Set the file to write the report to.
+        ///
+        /// The report embeds its data, styles and scripts, and references no
+        /// other resource.
+        output: PathBuf,
+
+        #[arg(short, long)]
+        /// Set the title of the report.
+        ///
+        /// It defaults to the name of the first input.
+        title: Option<String>,
+
+        #[arg(long)]
+        /// Read the colors of the series from the given TOML file, see the
+        /// `plot` command.
+        theme: Option<PathBuf>,
+    },
+
+    /// Print tables summarizing results
+    Summarize {
+        #[arg(required = true)]
+        /// Read results from the given CSV files, or from every '*.csv' of the
+        /// given directories.
+        ///
+        /// Files are expected to be named '<workload>.<hasher>.csv'.
+        input: Vec<PathBuf>,
+
+        #[arg(short, long, default_value = "markdown")]
+        /// Set the format of the tables.
+        ///
+        /// Must be 'markdown'.
+        format: SummaryFormat,
+
+        #[arg(long, num_args = 0..=1, default_missing_value = "README.md")]
+        /// Rewrite the region of the given file between the
+        /// '<!-- summary:begin -->' and '<!-- summary:end -->' lines instead
+        /// of printing the tables, 'README.md' by default.
+        readme: Option<PathBuf>,
+    },
+
+    /// Print a palette of evenly spaced colors
+    Palette {
+        #[arg(short('n'), long, default_value = "16")]
+        /// Set the number of colors.
+        count: usize,
+
+        #[arg(short, long)]
+        /// Print a theme assigning a color to every map family of the given
+        /// CSV files or directories instead.
+        input: Vec<PathBuf>,
     },
 }
 
+/// The initial capacity of the maps in every workload, as a power of two.
+pub(crate) const INITIAL_CAPACITY_LOG2: u8 = 25;
+
+/// The capacity of the caches in the cache workload, as a power of two.
+pub(crate) const CACHE_CAPACITY_LOG2: u8 = 20;
+
+/// The number of distinct keys looked up in the cache workload, as a power of
+/// two.
+pub(crate) const CACHE_KEYS_LOG2: u8 = 22;
+
+/// The exponent of the Zipf distribution of the keys looked up in the cache
+/// workload, the one of YCSB.
+pub(crate) const CACHE_ZIPF_EXPONENT: f64 = 0.99;
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum WorkloadType {
     /// Read-heavy workload.
@@ -91,6 +393,10 @@ pub(crate) enum WorkloadType {
 
     /// Rapid grow workload.
     RapidGrow,
+
+    /// Read-through cache workload, for maps evicting their least recently
+    /// used entries.
+    Cache,
 }
 
 impl FromStr for WorkloadType {
@@ -103,6 +409,7 @@ impl FromStr for WorkloadType {
             "exchange" => Ok(Self::Exchange),
             "rapidgrow" => Ok(Self::RapidGrow),
             "rapid_grow" => Ok(Self::RapidGrow),
+            "cache" => Ok(Self::Cache),
 
             _ => Err("unknown workload"),
         }
@@ -110,17 +417,34 @@ impl FromStr for WorkloadType {
 }
 
 impl WorkloadType {
-    /// Create a [bustle::Workload] based on the given options.
-    pub(crate) fn create(&self, threads: u32, operations: f64) -> bustle::Workload {
+    /// Run the workload on `C` with the given options, returning the hit ratio
+    /// of the cache workload as well.
+    ///
+    /// Maps run a [bustle::Workload], and caches the loop of [crate::cache].
+    pub(crate) fn run<C>(&self, threads: u32, operations: f64) -> (bustle::Measurement, Option<f64>)
+    where
+        C: bustle::Collection,
+        <C::Handle as bustle::CollectionHandle>::Key: Send + Debug + From<u64>,
+    {
         let mut workload = match self {
             Self::ReadHeavy => Self::read_heavy(threads),
             Self::Exchange => Self::exchange(threads),
             Self::RapidGrow => Self::rapid_grow(threads),
+            Self::Cache => {
+                let (measurement, hit_ratio) = crate::cache::run::<C>(threads, operations);
+                return (measurement, Some(hit_ratio));
+            }
         };
 
         workload.operations(operations);
 
-        workload
+        (workload.run_silently::<C>(), None)
+    }
+
+    /// The number of operations each thread performs in the workload mix, the
+    /// same way bustle computes it.
+    pub(crate) fn ops_per_thread(threads: u32, operations: f64) -> usize {
+        ((1usize << INITIAL_CAPACITY_LOG2) as f64 * operations) as usize / threads as usize
     }
 
     fn read_heavy(threads: u32) -> bustle::Workload {
@@ -133,7 +457,7 @@ impl WorkloadType {
         };
 
         *bustle::Workload::new(threads as usize, mix)
-            .initial_capacity_log2(25)
+            .initial_capacity_log2(INITIAL_CAPACITY_LOG2)
             .prefill_fraction(0.75)
     }
 
@@ -147,7 +471,7 @@ impl WorkloadType {
         };
 
         *bustle::Workload::new(threads as usize, mix)
-            .initial_capacity_log2(25)
+            .initial_capacity_log2(INITIAL_CAPACITY_LOG2)
             .prefill_fraction(0.0)
     }
 
@@ -161,7 +485,7 @@ impl WorkloadType {
         };
 
         *bustle::Workload::new(threads as usize, mix)
-            .initial_capacity_log2(25)
+            .initial_capacity_log2(INITIAL_CAPACITY_LOG2)
             .prefill_fraction(0.75)
     }
 }
@@ -186,3 +510,721 @@ impl FromStr for HasherKind {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Allocator {
+    /// mimalloc, with the `alloc_mimalloc` feature.
+    Mimalloc,
+
+    /// jemalloc, with the `alloc_jemalloc` feature.
+    Jemalloc,
+
+    /// The allocator of the system, always available.
+    System,
+}
+
+impl Allocator {
+    /// Every allocator, in order of preference.
+    pub(crate) const ALL: [Self; 3] = [Self::Mimalloc, Self::Jemalloc, Self::System];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Mimalloc => "mimalloc",
+            Self::Jemalloc => "jemalloc",
+            Self::System => "system",
+        }
+    }
+
+    /// Whether the allocator is compiled in.
+    pub(crate) fn is_available(self) -> bool {
+        match self {
+            Self::Mimalloc => cfg!(feature = "alloc_mimalloc"),
+            Self::Jemalloc => cfg!(feature = "alloc_jemalloc"),
+            Self::System => true,
+        }
+    }
+}
+
+impl FromStr for Allocator {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mimalloc" => Ok(Self::Mimalloc),
+            "jemalloc" => Ok(Self::Jemalloc),
+            "system" => Ok(Self::System),
+            _ => Err("invalid allocator, must be one of 'mimalloc', 'jemalloc' or 'system'"),
+        }
+    }
+}
+
+impl fmt::Display for Allocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ImageFormat {
+    /// SVG, with text converted to outlines.
+    Svg,
+
+    /// PNG, rasterized with the embedded font.
+    Png,
+}
+
+impl FromStr for ImageFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "svg" => Ok(Self::Svg),
+            "png" => Ok(Self::Png),
+            _ => Err("invalid image format, must be one of 'svg' or 'png'"),
+        }
+    }
+}
+
+impl ImageFormat {
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PlotKind {
+    /// Absolute throughput against threads.
+    Throughput,
+
+    /// Mean latency against threads.
+    Latency,
+
+    /// Jain's fairness index against threads.
+    Fairness,
+
+    /// Throughput relative to the single-threaded throughput of each series.
+    Speedup,
+
+    /// Speedup divided by the number of threads.
+    Efficiency,
+
+    /// Throughput of every map at a single thread count, as sorted bars.
+    Bars,
+
+    /// Throughput of every map family across workloads or thread counts.
+    Heatmap,
+}
+
+impl FromStr for PlotKind {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "throughput" => Ok(Self::Throughput),
+            "latency" => Ok(Self::Latency),
+            "fairness" => Ok(Self::Fairness),
+            "speedup" => Ok(Self::Speedup),
+            "efficiency" => Ok(Self::Efficiency),
+            "bars" => Ok(Self::Bars),
+            "heatmap" => Ok(Self::Heatmap),
+            _ => Err("invalid plot kind, must be one of 'throughput', 'latency', 'fairness', 'speedup', 'efficiency', 'bars' or 'heatmap'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AtThreads {
+    /// The most threads each map ran with.
+    Max,
+
+    /// A fixed number of threads.
+    Count(u32),
+}
+
+impl FromStr for AtThreads {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "max" => Ok(Self::Max),
+            n => n
+                .parse()
+                .map(Self::Count)
+                .map_err(|_| "invalid thread count, must be a number or 'max'"),
+        }
+    }
+}
+
+impl fmt::Display for AtThreads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Max => write!(f, "max threads"),
+            Self::Count(n) => write!(f, "{} threads", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum LatencyAxis {
+    /// Up to `--latency-limit-ns`.
+    Limit,
+
+    /// Up to a percentile of every latency, from 0 to 100.
+    Percentile(f64),
+
+    /// A log scale fitting every latency.
+    Log,
+}
+
+impl FromStr for LatencyAxis {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err =
+            "invalid latency axis, must be one of 'limit', 'log' or 'p<percentile>', e.g. 'p90'";
+
+        match s.to_ascii_lowercase().as_str() {
+            "limit" => Ok(Self::Limit),
+            "log" => Ok(Self::Log),
+            p => match p.strip_prefix('p').map(str::parse) {
+                Some(Ok(p)) if (0.0..=100.0).contains(&p) => Ok(Self::Percentile(p)),
+                _ => Err(err),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BarGroup {
+    /// One group per input.
+    Source,
+
+    /// One group per workload, the part of the input name before the hasher.
+    Workload,
+
+    /// One group per hasher, the last part of the input name.
+    Hasher,
+}
+
+impl FromStr for BarGroup {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "source" => Ok(Self::Source),
+            "workload" => Ok(Self::Workload),
+            "hasher" => Ok(Self::Hasher),
+            _ => Err("invalid bar grouping, must be one of 'source', 'workload' or 'hasher'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HeatmapColumns {
+    /// One column per input, at the thread count set by `--at-threads`.
+    Workload,
+
+    /// One column per thread count.
+    Threads,
+}
+
+impl FromStr for HeatmapColumns {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "workload" => Ok(Self::Workload),
+            "threads" => Ok(Self::Threads),
+            _ => Err("invalid heatmap columns, must be one of 'workload' or 'threads'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HeatmapRelative {
+    /// Relative to the best cell of the row.
+    Row,
+
+    /// Relative to the best cell of the column.
+    Column,
+}
+
+impl FromStr for HeatmapRelative {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "row" => Ok(Self::Row),
+            "column" => Ok(Self::Column),
+            _ => Err("invalid heatmap coloring, must be one of 'row' or 'column'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GroupBy {
+    /// One set of plots per input.
+    Source,
+
+    /// One set of plots per map family, with a series per input.
+    Family,
+
+    /// One set of plots per hasher, with a series per map and workload.
+    Hasher,
+
+    /// One set of plots per workload, with a series per map and hasher.
+    Workload,
+}
+
+impl FromStr for GroupBy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "source" => Ok(Self::Source),
+            "family" => Ok(Self::Family),
+            "hasher" => Ok(Self::Hasher),
+            "workload" => Ok(Self::Workload),
+            _ => Err("invalid grouping, must be one of 'family', 'hasher', 'workload' or 'source'"),
+        }
+    }
+}
+
+/// Renames a series, see `--rename`.
+#[derive(Debug, Clone)]
+pub(crate) struct Rename {
+    from: String,
+    to: String,
+}
+
+impl Rename {
+    /// The new name of the series `name`, if renamed.
+    ///
+    /// Renaming a crate keeps the version and label of its series, e.g.
+    /// `dashmap5=dashmap` renames `dashmap5@5.5.3` to `dashmap@5.5.3`.
+    pub(crate) fn apply(&self, name: &str) -> Option<String> {
+        if name == self.from {
+            return Some(self.to.clone());
+        }
+
+        let rest = name.strip_prefix(&self.from)?.strip_prefix('@')?;
+        Some(format!("{}@{}", self.to, rest))
+    }
+}
+
+impl FromStr for Rename {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok(Self {
+                from: from.to_owned(),
+                to: to.to_owned(),
+            }),
+            _ => Err("invalid rename, must be 'old=new'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SummaryFormat {
+    /// GitHub flavored markdown tables.
+    Markdown,
+}
+
+impl FromStr for SummaryFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => Err("invalid summary format, must be 'markdown'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TerminalChars {
+    /// Braille dots, 2x4 per character.
+    Braille,
+
+    /// Half blocks, 1x2 per character, for fonts lacking braille.
+    Block,
+}
+
+impl FromStr for TerminalChars {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "braille" => Ok(Self::Braille),
+            "block" => Ok(Self::Block),
+            _ => Err("invalid terminal characters, must be one of 'braille' or 'block'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum PinPolicy {
+    /// Let the OS schedule the threads.
+    None,
+
+    /// Fill one package at a time, physical cores first.
+    Compact,
+
+    /// Spread across packages, physical cores first.
+    Scatter,
+
+    /// Pin the i-th thread to the i-th CPU of the list.
+    List(Vec<usize>),
+}
+
+impl FromStr for PinPolicy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "compact" => Ok(Self::Compact),
+            "scatter" => Ok(Self::Scatter),
+            _ => match s.strip_prefix("list:").map(crate::affinity::parse_cpu_list) {
+                Some(Some(cpus)) if !cpus.is_empty() => Ok(Self::List(cpus)),
+                Some(_) => Err(
+                    "invalid CPU list, expected e.g. 'list:0-3,8' with every CPU below CPU_SETSIZE",
+                ),
+                None => Err(
+                    "invalid pin policy, must be one of 'none', 'compact', 'scatter' or 'list:<cpus>'",
+                ),
+            },
+        }
+    }
+}
+
+impl fmt::Display for PinPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::Compact => f.write_str("compact"),
+            Self::Scatter => f.write_str("scatter"),
+            Self::List(cpus) => {
+                f.write_str("list:")?;
+
+                for (i, cpu) in cpus.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+
+                    write!(f, "{cpu}")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ThreadsPolicy {
+    /// Every count up to the number of available CPUs, with coarser steps on
+    /// larger machines.
+    Linear,
+
+    /// Powers of two up to the number of available CPUs.
+    Pow2,
+
+    /// Like [`ThreadsPolicy::Linear`], always including the number of
+    /// physical cores.
+    PhysicalCores,
+
+    /// Like [`ThreadsPolicy::PhysicalCores`], going up to the given multiple
+    /// of the number of available CPUs.
+    Oversubscribe(f64),
+}
+
+impl FromStr for ThreadsPolicy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Self::Linear),
+            "pow2" => Ok(Self::Pow2),
+            "physical-cores" | "physical_cores" => Ok(Self::PhysicalCores),
+            _ => match s.strip_prefix("oversubscribe:").map(str::parse::<f64>) {
+                Some(Ok(factor)) if factor >= 1.0 => Ok(Self::Oversubscribe(factor)),
+                Some(_) => Err("invalid oversubscribe factor, must be a number >= 1"),
+                None => Err("invalid threads policy, must be one of 'linear', 'pow2', 'physical-cores' or 'oversubscribe:<factor>'"),
+            },
+        }
+    }
+}
+
+impl ThreadsPolicy {
+    /// The thread counts to sweep, in ascending order.
+    pub(crate) fn thread_counts(&self) -> Vec<u32> {
+        // `num_cpus::get` already accounts for the affinity mask and cgroup
+        // CPU quota, while the physical core count accounts for neither.
+        let logical = num_cpus::get().max(1) as u32;
+        let physical = match crate::affinity::allowed_cores() {
+            Ok((allowed, physical)) => Self::physical_under_quota(physical, allowed, logical),
+            Err(_) => num_cpus::get_physical() as u32,
+        };
+
+        self.thread_counts_for(logical, physical.clamp(1, logical))
+    }
+
+    /// Scale the `physical` cores among the `allowed` CPUs of the process
+    /// down to the share of them its CPU quota leaves, `logical` CPUs.
+    fn physical_under_quota(physical: usize, allowed: usize, logical: u32) -> u32 {
+        match allowed {
+            0 => physical as u32,
+            _ => (physical as u64 * logical.min(allowed as u32) as u64).div_ceil(allowed as u64)
+                as u32,
+        }
+    }
+
+    /// The thread counts to sweep with `logical` available CPUs, `physical`
+    /// of which are distinct cores.
+    fn thread_counts_for(&self, logical: u32, physical: u32) -> Vec<u32> {
+        let mut counts = match self {
+            Self::Linear => return Self::linear(logical),
+            Self::Pow2 => (0..u32::BITS)
+                .map(|i| 1 << i)
+                .take_while(|n| *n < logical)
+                .chain([logical])
+                .collect(),
+            Self::PhysicalCores => Self::linear(logical)
+                .into_iter()
+                .chain([physical])
+                .collect(),
+            Self::Oversubscribe(factor) => {
+                let max = (logical as f64 * factor).ceil() as u32;
+
+                Self::linear(max)
+                    .into_iter()
+                    .chain([physical, logical, max])
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        counts.sort_unstable();
+        counts.dedup();
+        counts
+    }
+
+    fn linear(n: u32) -> Vec<u32> {
+        match n {
+            0..=10 => (1..=n).collect(),
+            11..=16 => std::iter::once(1)
+                .chain((0..=n).step_by(2).skip(1))
+                .collect(),
+            _ => std::iter::once(1)
+                .chain((0..=n).step_by(4).skip(1))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ThreadsRange {
+    start: u32,
+    end: u32,
+    step: usize,
+}
+
+impl FromStr for ThreadsRange {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERR: &str = "invalid threads range, expected e.g. '1..16' or '1..16:2'";
+
+        let (range, step) = s.split_once(':').unwrap_or((s, "1"));
+        let (start, end) = range.split_once("..").ok_or(ERR)?;
+
+        let range = Self {
+            start: start.parse().map_err(|_| ERR)?,
+            end: end.parse().map_err(|_| ERR)?,
+            step: step.parse().map_err(|_| ERR)?,
+        };
+
+        if range.start == 0 || range.start > range.end || range.step == 0 {
+            return Err(ERR);
+        }
+
+        Ok(range)
+    }
+}
+
+impl ThreadsRange {
+    /// The thread counts to sweep, in ascending order.
+    pub(crate) fn thread_counts(&self) -> Vec<u32> {
+        (self.start..=self.end).step_by(self.step).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(s: &str) -> Result<Vec<u32>, &'static str> {
+        s.parse::<ThreadsRange>().map(|range| range.thread_counts())
+    }
+
+    #[test]
+    fn parses_threads_ranges() {
+        assert_eq!(range("1..4"), Ok(vec![1, 2, 3, 4]));
+        assert_eq!(range("1..1"), Ok(vec![1]));
+        assert_eq!(range("1..16:4"), Ok(vec![1, 5, 9, 13]));
+        assert_eq!(range("2..3:8"), Ok(vec![2]));
+    }
+
+    #[test]
+    fn rejects_invalid_threads_ranges() {
+        assert!(range("1..8:0").is_err());
+        assert!(range("8..1").is_err());
+        assert!(range("0..4").is_err());
+        assert!(range("1..").is_err());
+        assert!(range("1-4").is_err());
+        assert!(range("1..4:").is_err());
+        assert!(range("1..-4").is_err());
+    }
+
+    #[test]
+    fn parses_threads_policies() {
+        assert!(matches!("linear".parse(), Ok(ThreadsPolicy::Linear)));
+        assert!(matches!("pow2".parse(), Ok(ThreadsPolicy::Pow2)));
+        assert!(matches!(
+            "physical-cores".parse(),
+            Ok(ThreadsPolicy::PhysicalCores)
+        ));
+        assert!(matches!(
+            "oversubscribe:1.5".parse(),
+            Ok(ThreadsPolicy::Oversubscribe(factor)) if factor == 1.5
+        ));
+        assert!("quadratic".parse::<ThreadsPolicy>().is_err());
+        assert!("oversubscribe:0.5".parse::<ThreadsPolicy>().is_err());
+        assert!("oversubscribe:x".parse::<ThreadsPolicy>().is_err());
+    }
+
+    #[test]
+    fn sweeps_thread_counts() {
+        assert_eq!(ThreadsPolicy::Linear.thread_counts_for(4, 2), [1, 2, 3, 4]);
+        assert_eq!(
+            ThreadsPolicy::Linear.thread_counts_for(16, 8),
+            [1, 2, 4, 6, 8, 10, 12, 14, 16]
+        );
+        assert_eq!(
+            ThreadsPolicy::Pow2.thread_counts_for(12, 6),
+            [1, 2, 4, 8, 12]
+        );
+        assert_eq!(
+            ThreadsPolicy::PhysicalCores.thread_counts_for(12, 5),
+            [1, 2, 4, 5, 6, 8, 10, 12]
+        );
+        assert_eq!(
+            ThreadsPolicy::Oversubscribe(1.5).thread_counts_for(4, 2),
+            [1, 2, 3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn scales_physical_cores_under_quota() {
+        // No quota: every allowed physical core.
+        assert_eq!(ThreadsPolicy::physical_under_quota(8, 16, 16), 8);
+        // A quota of 4 CPUs out of 16 SMT siblings.
+        assert_eq!(ThreadsPolicy::physical_under_quota(8, 16, 4), 2);
+        // Rounded up, never below a core.
+        assert_eq!(ThreadsPolicy::physical_under_quota(8, 16, 1), 1);
+        assert_eq!(ThreadsPolicy::physical_under_quota(3, 6, 5), 3);
+    }
+
+    #[test]
+    fn parses_pin_policies() {
+        assert!(matches!("none".parse(), Ok(PinPolicy::None)));
+        assert!(matches!("compact".parse(), Ok(PinPolicy::Compact)));
+        assert!(matches!("scatter".parse(), Ok(PinPolicy::Scatter)));
+        assert!(matches!(
+            "list:0-3,8".parse(),
+            Ok(PinPolicy::List(cpus)) if cpus == [0, 1, 2, 3, 8]
+        ));
+        assert!("spread".parse::<PinPolicy>().is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_pin_lists() {
+        assert!("list:".parse::<PinPolicy>().is_err());
+        assert!("list:3-1".parse::<PinPolicy>().is_err());
+        assert!("list:0,x".parse::<PinPolicy>().is_err());
+        assert!(format!("list:0,{}", crate::affinity::CPU_SETSIZE)
+            .parse::<PinPolicy>()
+            .is_err());
+    }
+
+    #[test]
+    fn pin_lists_round_trip() {
+        let policy = "list:0-2,8".parse::<PinPolicy>().unwrap();
+
+        assert_eq!(policy.to_string(), "list:0,1,2,8");
+        assert!(matches!(
+            policy.to_string().parse(),
+            Ok(PinPolicy::List(cpus)) if cpus == [0, 1, 2, 8]
+        ));
+    }
+
+    fn rename(rule: &str, name: &str) -> Option<String> {
+        rule.parse::<Rename>().unwrap().apply(name)
+    }
+
+    #[test]
+    fn renames_series_and_crates() {
+        assert_eq!(rename("std=std(std)", "std"), Some("std(std)".into()));
+        assert_eq!(
+            rename("dashmap5=dashmap", "dashmap5@5.5.3"),
+            Some("dashmap@5.5.3".into())
+        );
+        assert_eq!(
+            rename("scc=scc2", "scc@2.1.16 - HashMap"),
+            Some("scc2@2.1.16 - HashMap".into())
+        );
+
+        // Only whole crate names are renamed.
+        assert_eq!(rename("dashmap=dm", "dashmap5@5.5.3"), None);
+        assert_eq!(rename("std=s", "std(parking_lot)"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_renames() {
+        assert!("dashmap".parse::<Rename>().is_err());
+        assert!("=dashmap".parse::<Rename>().is_err());
+        assert!("dashmap=".parse::<Rename>().is_err());
+    }
+
+    #[test]
+    fn parses_series_filters() {
+        let include = "^dashmap".parse::<Regex>().unwrap();
+        let exclude = "(a*)*b".parse::<Regex>().unwrap();
+
+        assert!(include.is_match("dashmap5@5.5.3"));
+        assert!(!include.is_match("std(dashmap)"));
+        // Nested quantifiers run in linear time.
+        assert!(!exclude.is_match(&"a".repeat(10_000)));
+
+        assert!("(dash".parse::<Regex>().is_err());
+    }
+
+    #[test]
+    fn parses_latency_axes() {
+        assert!(matches!("limit".parse(), Ok(LatencyAxis::Limit)));
+        assert!(matches!("LOG".parse(), Ok(LatencyAxis::Log)));
+        assert!(matches!("p90".parse(), Ok(LatencyAxis::Percentile(p)) if p == 90.));
+        assert!(matches!("P99.5".parse(), Ok(LatencyAxis::Percentile(p)) if p == 99.5));
+        assert!(matches!("p0".parse(), Ok(LatencyAxis::Percentile(p)) if p == 0.));
+        assert!(matches!("p100".parse(), Ok(LatencyAxis::Percentile(p)) if p == 100.));
+
+        for invalid in ["p", "p101", "p-1", "px", "90", "linear"] {
+            assert!(invalid.parse::<LatencyAxis>().is_err(), "{}", invalid);
+        }
+    }
+}
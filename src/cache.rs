@@ -0,0 +1,281 @@
+//! A read-through cache workload, for maps evicting their least recently used
+//! entries.
+//!
+//! bustle asserts that every insert adds a key and that every read finds the
+//! keys inserted before, which evicting maps break, so the cache workload runs
+//! its own loop: every thread looks up Zipf distributed keys, `get` promoting
+//! the entries it hits, and inserts the keys it misses, `insert` evicting the
+//! least recently used entry once the cache is full. Lookups and inserts both
+//! count as operations, the same way the fairness probe counts them.
+
+use std::{
+    hash::{BuildHasher, RandomState},
+    sync::Barrier,
+    thread,
+    time::{Duration, Instant},
+};
+
+use bustle::{Collection, CollectionHandle, Measurement};
+
+use crate::args::{WorkloadType, CACHE_CAPACITY_LOG2, CACHE_KEYS_LOG2, CACHE_ZIPF_EXPONENT};
+
+/// Run the cache workload on `C` with `threads` threads, and return its hit
+/// ratio as well.
+pub(crate) fn run<C>(threads: u32, operations: f64) -> (Measurement, f64)
+where
+    C: Collection,
+    <C::Handle as CollectionHandle>::Key: Send + From<u64>,
+{
+    let capacity = 1 << CACHE_CAPACITY_LOG2;
+    let ops_per_thread = WorkloadType::ops_per_thread(threads, operations);
+    let zipf = Zipf::new((1u64 << CACHE_KEYS_LOG2) as f64, CACHE_ZIPF_EXPONENT);
+
+    // At most one key per operation, drawn before the clock starts.
+    let keys = thread::scope(|s| {
+        (0..threads)
+            .map(|_| {
+                let mut rng = SplitMix64(RandomState::new().hash_one(0));
+
+                s.spawn(move || {
+                    (0..ops_per_thread)
+                        .map(|_| key(zipf.sample(&mut rng) as u64))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let table = C::with_capacity(capacity);
+    let barrier = Barrier::new(threads as usize + 1);
+
+    let (spent, hits, lookups) = thread::scope(|s| {
+        // Warm the cache up with the hottest keys, the hottest inserted last.
+        s.spawn(|| {
+            let mut table = table.pin();
+
+            for rank in (1..=capacity as u64).rev() {
+                table.insert(&key(rank).into());
+            }
+        })
+        .join()
+        .unwrap();
+
+        let workers = keys
+            .iter()
+            .map(|keys| {
+                let (table, barrier) = (&table, &barrier);
+
+                s.spawn(move || {
+                    let mut table = table.pin();
+                    let (mut ops, mut hits, mut lookups) = (0, 0, 0);
+
+                    barrier.wait();
+
+                    for &key in keys {
+                        if ops == ops_per_thread {
+                            break;
+                        }
+
+                        let key = key.into();
+                        lookups += 1;
+                        ops += 1;
+
+                        if table.get(&key) {
+                            hits += 1;
+                        } else if ops < ops_per_thread {
+                            table.insert(&key);
+                            ops += 1;
+                        }
+                    }
+
+                    barrier.wait();
+                    (hits, lookups)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        barrier.wait();
+        let start = Instant::now();
+        barrier.wait();
+        let spent = start.elapsed();
+
+        let (hits, lookups) = workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .fold((0u64, 0u64), |acc, (hits, lookups)| {
+                (acc.0 + hits, acc.1 + lookups)
+            });
+
+        (spent, hits, lookups)
+    });
+
+    drop(table);
+
+    let total_ops = (ops_per_thread * threads as usize) as u64;
+    let measurement = Measurement {
+        seed: [0; 32],
+        total_ops,
+        spent,
+        throughput: total_ops as f64 / spent.as_secs_f64(),
+        latency: Duration::from_nanos((spent * threads).as_nanos() as u64 / total_ops.max(1)),
+    };
+
+    let hit_ratio = hits as f64 / lookups.max(1) as f64;
+
+    (measurement, hit_ratio)
+}
+
+/// The key of a rank, scattered so that hot keys are not neighbours.
+fn key(rank: u64) -> u64 {
+    SplitMix64(rank).next()
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform float in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Zipf distributed ranks in `1..=n`, sampled by rejection-inversion
+/// (Hörmann and Derflinger, 1996) as in `rand_distr`.
+#[derive(Clone, Copy)]
+struct Zipf {
+    s: f64,
+    t: f64,
+    q: f64,
+}
+
+impl Zipf {
+    fn new(n: f64, s: f64) -> Self {
+        let q = if s != 1. { 1. / (1. - s) } else { 0. };
+        let t = if s != 1. {
+            (n.powf(1. - s) - s) * q
+        } else {
+            1. + n.ln()
+        };
+
+        Self { s, t, q }
+    }
+
+    fn inv_cdf(&self, p: f64) -> f64 {
+        let pt = p * self.t;
+
+        if pt <= 1. {
+            pt
+        } else if self.s != 1. {
+            (pt * (1. - self.s) + self.s).powf(self.q)
+        } else {
+            (pt - 1.).exp()
+        }
+    }
+
+    fn sample(&self, rng: &mut SplitMix64) -> f64 {
+        loop {
+            let inv_b = self.inv_cdf(rng.next_f64());
+            let x = (inv_b + 1.).floor();
+            let mut ratio = x.powf(-self.s);
+
+            if x > 1. {
+                ratio *= inv_b.powf(self.s);
+            }
+
+            if rng.next_f64() < ratio {
+                return x;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The frequency of every rank of `1..=n` over `samples` samples.
+    fn frequencies(n: usize, s: f64, samples: usize) -> Vec<f64> {
+        let zipf = Zipf::new(n as f64, s);
+        let mut rng = SplitMix64(42);
+        let mut counts = vec![0; n + 1];
+
+        for _ in 0..samples {
+            let rank = zipf.sample(&mut rng);
+            assert!(rank >= 1. && rank <= n as f64 && rank.fract() == 0.);
+            counts[rank as usize] += 1;
+        }
+
+        counts.iter().map(|c| *c as f64 / samples as f64).collect()
+    }
+
+    /// The probability of `rank` under a Zipf law over `1..=n`.
+    fn expected(rank: usize, n: usize, s: f64) -> f64 {
+        let norm = (1..=n).map(|k| (k as f64).powf(-s)).sum::<f64>();
+        (rank as f64).powf(-s) / norm
+    }
+
+    #[test]
+    fn zipf_follows_the_law() {
+        for s in [0.5, 1.0, CACHE_ZIPF_EXPONENT, 1.5] {
+            let n = 100;
+            let freq = frequencies(n, s, 200_000);
+
+            for rank in [1, 2, 10] {
+                let expected = expected(rank, n, s);
+                let error = (freq[rank] - expected).abs() / expected;
+
+                assert!(
+                    error < 0.05,
+                    "s={} rank={}: {} instead of {}",
+                    s,
+                    rank,
+                    freq[rank],
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn zipf_of_a_single_rank() {
+        let zipf = Zipf::new(1., CACHE_ZIPF_EXPONENT);
+        let mut rng = SplitMix64(7);
+
+        assert!((0..1000).all(|_| zipf.sample(&mut rng) == 1.));
+    }
+
+    #[test]
+    fn uniform_floats() {
+        let mut rng = SplitMix64(0);
+
+        let mean = (0..100_000)
+            .map(|_| rng.next_f64())
+            .inspect(|x| assert!((0.0..1.0).contains(x)))
+            .sum::<f64>()
+            / 100_000.;
+
+        assert!((mean - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn keys_are_scattered() {
+        let keys = (1..=1000)
+            .map(key)
+            .collect::<std::collections::HashSet<_>>();
+
+        assert_eq!(keys.len(), 1000);
+        assert!(key(1).abs_diff(key(2)) > 1 << 32);
+    }
+}
@@ -1,82 +1,51 @@
+//! Exact versions of the dependencies, resolved from `Cargo.lock` by the build
+//! script.
+
 use std::{
     collections::HashMap,
+    fmt,
     sync::{Arc, LazyLock},
 };
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-pub(crate) enum DependencyValue {
-    String(Arc<str>),
-    Object {
-        package: Option<Arc<str>>,
-        version: Arc<str>,
-        features: Option<Vec<Arc<str>>>,
-        optional: Option<bool>,
-    },
-    Git {
-        package: Option<Arc<str>>,
-        git: Arc<str>,
-        branch: Option<Arc<str>>,
-        rev: Option<Arc<str>>,
-        version: Option<Arc<str>>,
-        features: Option<Vec<Arc<str>>>,
-        optional: Option<bool>,
-    },
+pub(crate) struct Dependency {
+    /// The real package name, which may differ from the dependency key when
+    /// the dependency is renamed (e.g. `dashmap5`).
+    pub package: Arc<str>,
+    /// The locked version, which names the bench cases. It is the same for
+    /// every commit of a git dependency, so that lock bumps keep the names,
+    /// and the commit is recorded in the source instead.
+    pub version: Arc<str>,
+    /// `registry+...` or `git+<url>#<commit>`, none for path dependencies.
+    pub source: Option<Arc<str>>,
+    /// Checksum of the crate file, only available for registry dependencies.
+    pub checksum: Option<Arc<str>>,
 }
 
-impl DependencyValue {
-    pub(crate) fn version(&self) -> Option<Arc<str>> {
-        match self {
-            Self::String(s) => Some(s.clone()),
-            Self::Object { version, .. } => Some(version.clone()),
-            Self::Git {
-                git, branch, rev, ..
-            } => Some(Arc::from({
-                let mut git_url = String::with_capacity(128);
-                git_url.push_str(git);
-
-                if let Some(branch) = branch {
-                    git_url.push_str("?branch=");
-                    git_url.push_str(branch);
-                }
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.package, self.version)?;
 
-                if let Some(rev) = rev {
-                    git_url.push('#');
-                    git_url.push_str(rev);
-                }
+        if let Some(source) = &self.source {
+            write!(f, " ({source})")?;
+        }
 
-                git_url
-            })),
+        if let Some(checksum) = &self.checksum {
+            write!(f, " [{checksum}]")?;
         }
-    }
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CargoToml {
-    dependencies: HashMap<String, DependencyValue>,
+        Ok(())
+    }
 }
 
-pub(crate) static DEPS: LazyLock<HashMap<String, DependencyValue>> = LazyLock::new(|| {
-    CargoToml::dependencies().unwrap_or_else(|e| {
-        unreachable!("Failed to parse Cargo.toml: {:#?}", e);
+pub(crate) static DEPS: LazyLock<HashMap<String, Dependency>> = LazyLock::new(|| {
+    toml::from_str(include_str!(concat!(env!("OUT_DIR"), "/deps.toml"))).unwrap_or_else(|e| {
+        unreachable!("Failed to parse resolved dependencies: {:#?}", e);
     })
 });
 
-impl CargoToml {
-    fn dependencies() -> Result<HashMap<String, DependencyValue>, toml::de::Error> {
-        let cargo_toml_raw = include_str!("../Cargo.toml");
-        let cargo_toml: CargoToml = toml::from_str(cargo_toml_raw)?;
-
-        Ok(cargo_toml
-            .dependencies
-            .into_iter()
-            .map(|(k, v)| (k.replace("-", "_").to_string(), v))
-            .collect())
-    }
-}
-
 #[macro_export]
 macro_rules! dep_name_with_version {
     ($dep:ty, $name:literal) => {{
@@ -85,9 +54,8 @@ macro_rules! dep_name_with_version {
             "{dep_name}@{} - {}",
             $crate::deps::DEPS
                 .get(stringify!($dep))
-                .and_then($crate::deps::DependencyValue::version)
-                .unwrap()
-                .trim_start_matches('='),
+                .map(|dep| &dep.version)
+                .unwrap(),
             $name,
         )
     }};
@@ -97,9 +65,8 @@ macro_rules! dep_name_with_version {
             stringify!($dep),
             $crate::deps::DEPS
                 .get(stringify!($dep))
-                .and_then($crate::deps::DependencyValue::version)
-                .unwrap()
-                .trim_start_matches('='),
+                .map(|dep| &dep.version)
+                .unwrap(),
         )
     }};
 }
@@ -0,0 +1,274 @@
+//! Per-thread fairness probe.
+//!
+//! bustle only reports the aggregate throughput of a run, which hides threads
+//! being starved by others. [`Probed`] wraps a collection and lets every handle
+//! record how many operations it completed and when it started and finished,
+//! from which the fairness metrics of the run are derived.
+
+use std::{
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Default)]
+struct Probe {
+    /// The number of operations each worker thread will perform.
+    expected_ops: usize,
+
+    /// Samples pushed by handles when dropped, in drop order.
+    samples: Mutex<Vec<ThreadSample>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ThreadSample {
+    ops: usize,
+    started: Option<Instant>,
+    finished: Option<Instant>,
+}
+
+static PROBE: LazyLock<Mutex<Arc<Probe>>> = LazyLock::new(Default::default);
+
+/// Prepare the probe for the next run, in which each worker thread performs
+/// `expected_ops` operations.
+pub(crate) fn arm(expected_ops: usize) {
+    *PROBE.lock().unwrap() = Arc::new(Probe {
+        expected_ops,
+        samples: Mutex::default(),
+    });
+}
+
+/// Fairness metrics of a single run.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Fairness {
+    /// Jain's fairness index of the per-thread throughput, in `(0, 1]`.
+    pub jain: f64,
+
+    /// The lowest per-thread throughput divided by the highest one.
+    pub min_max_ratio: f64,
+
+    /// Time between the first and the last thread finishing.
+    pub finish_spread: Duration,
+}
+
+/// Compute the fairness metrics of the last run with `threads` worker threads.
+///
+/// Returns `None` if any worker thread did not complete its operations.
+pub(crate) fn collect(threads: u32) -> Option<Fairness> {
+    let probe = PROBE.lock().unwrap().clone();
+    let samples = probe.samples.lock().unwrap();
+
+    // Prefill handles are all dropped before the workload mix starts, so the
+    // worker threads are the last ones to report.
+    let workers = samples.get(samples.len().checked_sub(threads as usize)?..)?;
+
+    metrics(workers)
+}
+
+/// Compute the fairness metrics of the given worker threads, measuring each
+/// one from the first of them starting.
+fn metrics(workers: &[ThreadSample]) -> Option<Fairness> {
+    let started = workers.iter().filter_map(|s| s.started).min()?;
+    let finished = workers
+        .iter()
+        .map(|s| s.finished)
+        .collect::<Option<Vec<_>>>()?;
+
+    let throughput = workers
+        .iter()
+        .zip(&finished)
+        .map(|(s, finished)| s.ops as f64 / (*finished - started).as_secs_f64())
+        .collect::<Vec<_>>();
+
+    let sum = throughput.iter().sum::<f64>();
+    let sum_sq = throughput.iter().map(|x| x * x).sum::<f64>();
+    let (min, max) = throughput.iter().fold((f64::MAX, 0f64), |res, cur| {
+        (res.0.min(*cur), res.1.max(*cur))
+    });
+
+    Some(Fairness {
+        jain: sum * sum / (throughput.len() as f64 * sum_sq),
+        min_max_ratio: min / max,
+        finish_spread: *finished.iter().max()? - *finished.iter().min()?,
+    })
+}
+
+/// A collection whose handles report to the armed probe.
+pub(crate) struct Probed<C> {
+    inner: C,
+    probe: Arc<Probe>,
+}
+
+impl<C> bustle::Collection for Probed<C>
+where
+    C: bustle::Collection,
+{
+    type Handle = ProbedHandle<C::Handle>;
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: C::with_capacity(capacity),
+            probe: PROBE.lock().unwrap().clone(),
+        }
+    }
+
+    fn pin(&self) -> Self::Handle {
+        ProbedHandle {
+            inner: self.inner.pin(),
+            probe: self.probe.clone(),
+            sample: ThreadSample {
+                ops: 0,
+                started: None,
+                finished: None,
+            },
+        }
+    }
+}
+
+pub(crate) struct ProbedHandle<H> {
+    inner: H,
+    probe: Arc<Probe>,
+    sample: ThreadSample,
+}
+
+impl<H> ProbedHandle<H> {
+    #[inline(always)]
+    fn before(&mut self) {
+        if self.sample.ops == 0 {
+            self.sample.started = Some(Instant::now());
+        }
+    }
+
+    #[inline(always)]
+    fn after(&mut self) {
+        self.sample.ops += 1;
+
+        if self.sample.ops == self.probe.expected_ops {
+            self.sample.finished = Some(Instant::now());
+        }
+    }
+}
+
+impl<H> Drop for ProbedHandle<H> {
+    fn drop(&mut self) {
+        self.probe.samples.lock().unwrap().push(self.sample);
+    }
+}
+
+impl<H> bustle::CollectionHandle for ProbedHandle<H>
+where
+    H: bustle::CollectionHandle,
+{
+    type Key = H::Key;
+
+    #[inline]
+    fn get(&mut self, key: &Self::Key) -> bool {
+        self.before();
+        let res = self.inner.get(key);
+        self.after();
+        res
+    }
+
+    #[inline]
+    fn insert(&mut self, key: &Self::Key) -> bool {
+        self.before();
+        let res = self.inner.insert(key);
+        self.after();
+        res
+    }
+
+    #[inline]
+    fn remove(&mut self, key: &Self::Key) -> bool {
+        self.before();
+        let res = self.inner.remove(key);
+        self.after();
+        res
+    }
+
+    #[inline]
+    fn update(&mut self, key: &Self::Key) -> bool {
+        self.before();
+        let res = self.inner.update(key);
+        self.after();
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Workers all started at `start`, each completing `ops` operations in
+    /// the given number of milliseconds.
+    fn workers(start: Instant, runs: &[(usize, u64)]) -> Vec<ThreadSample> {
+        runs.iter()
+            .map(|&(ops, millis)| ThreadSample {
+                ops,
+                started: Some(start),
+                finished: Some(start + Duration::from_millis(millis)),
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn equal_throughput_is_perfectly_fair() {
+        let start = Instant::now();
+        let fairness = metrics(&workers(start, &[(1000, 100); 4])).unwrap();
+
+        assert_close(fairness.jain, 1.0);
+        assert_close(fairness.min_max_ratio, 1.0);
+        assert_eq!(fairness.finish_spread, Duration::ZERO);
+    }
+
+    #[test]
+    fn jain_index_of_known_samples() {
+        let start = Instant::now();
+
+        // Throughputs of 1, 2, 3 and 4 ops/ms: 10² / (4 × 30).
+        let fairness = metrics(&workers(
+            start,
+            &[(100, 100), (200, 100), (300, 100), (400, 100)],
+        ))
+        .unwrap();
+
+        assert_close(fairness.jain, 100.0 / 120.0);
+        assert_close(fairness.min_max_ratio, 0.25);
+    }
+
+    #[test]
+    fn a_single_busy_thread_scores_one_over_n() {
+        let start = Instant::now();
+        let fairness = metrics(&workers(start, &[(1000, 100), (0, 100), (0, 100)])).unwrap();
+
+        assert_close(fairness.jain, 1.0 / 3.0);
+        assert_close(fairness.min_max_ratio, 0.0);
+    }
+
+    #[test]
+    fn throughput_accounts_for_finish_times() {
+        let start = Instant::now();
+
+        // The same operations in twice the time: throughputs of 10 and 5.
+        let fairness = metrics(&workers(start, &[(1000, 100), (1000, 200)])).unwrap();
+
+        assert_close(fairness.jain, 225.0 / (2.0 * 125.0));
+        assert_close(fairness.min_max_ratio, 0.5);
+        assert_eq!(fairness.finish_spread, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn unfinished_workers_have_no_metrics() {
+        let start = Instant::now();
+        let mut samples = workers(start, &[(1000, 100), (1000, 100)]);
+        samples[1].finished = None;
+
+        assert!(metrics(&samples).is_none());
+        assert!(metrics(&[]).is_none());
+    }
+}
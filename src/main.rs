@@ -1,24 +1,47 @@
-use std::{error::Error, fmt::Debug, io, sync::Arc};
+use std::{error::Error, fmt::Debug, io, sync::Arc, time::Duration};
 
 use bustle::Measurement;
 use clap::Parser;
+use fairness::Fairness;
 
 mod adapters;
+mod affinity;
+mod allocator;
 mod args;
+mod cache;
 mod deps;
+mod fairness;
 mod plot;
+mod reclaim;
 mod record;
+mod report;
+mod summary;
 
-#[cfg(feature = "alloc_mimalloc")]
-#[global_allocator]
-static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+/// The shard counts and intervals table types can be parameterized with.
+///
+/// Every value instantiates every table it applies to, so powers of four keep
+/// the build time in check.
+const TABLE_PARAMETERS: [usize; 6] = [1, 4, 16, 64, 256, 1024];
 
-#[cfg(feature = "alloc_jemalloc")]
-#[global_allocator]
-static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+/// Run `$body` with the constant `$n` equal to `$value`, one of
+/// [`TABLE_PARAMETERS`], to parameterize table types with it.
+macro_rules! with_table_parameter {
+    ($value:expr, $n:ident => $body:block) => {
+        with_table_parameter!(@arms $value, $n => $body; 1 4 16 64 256 1024)
+    };
+    (@arms $value:expr, $n:ident => $body:block; $($pow:literal)*) => {
+        match $value {
+            $($pow => {
+                const $n: usize = $pow;
+                $body
+            })*
+            value => unreachable!("{} is not one of {:?}", value, TABLE_PARAMETERS),
+        }
+    };
+}
 
-#[cfg(all(feature = "alloc_mimalloc", feature = "alloc_jemalloc"))]
-compile_error!("only one allocator can be specified");
+#[global_allocator]
+static GLOBAL: allocator::Dispatch = allocator::Dispatch;
 
 fn main() -> Result<(), Box<dyn Error>> {
     tracing_subscriber::fmt::init();
@@ -28,18 +51,73 @@ fn main() -> Result<(), Box<dyn Error>> {
             workload,
             operations,
             threads,
+            threads_range,
+            threads_policy,
             hasher,
             gc_sleep_ms,
+            pin,
+            shards,
+            shard_padding,
+            dashmap_shards,
+            guard_refresh,
+            allocator,
+            fairness,
             skip,
             csv,
             csv_no_headers,
         } => {
+            let threads = threads
+                .or_else(|| threads_range.map(|range| range.thread_counts()))
+                .unwrap_or_else(|| threads_policy.thread_counts());
+
+            let allocator = allocator::check(allocator)?;
+
+            if let Some(shards) = shards
+                .iter()
+                .find(|shards| !TABLE_PARAMETERS.contains(shards))
+            {
+                return Err(format!(
+                    "invalid shard count {}, must be one of {:?}",
+                    shards, TABLE_PARAMETERS
+                )
+                .into());
+            }
+
+            if let Some(shards) = dashmap_shards
+                .iter()
+                .find(|shards| **shards < 2 || !TABLE_PARAMETERS.contains(shards))
+            {
+                return Err(format!(
+                    "invalid dashmap shard amount {}, must be one of {:?}",
+                    shards,
+                    &TABLE_PARAMETERS[1..]
+                )
+                .into());
+            }
+
+            if let Some(ops) = guard_refresh
+                .iter()
+                .find(|ops| !TABLE_PARAMETERS.contains(ops))
+            {
+                return Err(format!(
+                    "invalid guard refresh interval {}, must be one of {:?}",
+                    ops, TABLE_PARAMETERS
+                )
+                .into());
+            }
+
+            // Fail early if the placement cannot be resolved.
+            affinity::arm(&pin, 1)?;
+
             let mut handler = if csv {
                 let mut wr = csv::WriterBuilder::new()
                     .has_headers(!csv_no_headers)
                     .from_writer(io::stderr());
+                let placement = pin.to_string();
+
+                Box::new(move |name: &str, n, m: &Measurement, stats: &RunStats| {
+                    let f = stats.fairness;
 
-                Box::new(move |name: &str, n, m: &Measurement| {
                     wr.serialize(record::Record {
                         name: name.into(),
                         total_ops: m.total_ops,
@@ -47,16 +125,54 @@ fn main() -> Result<(), Box<dyn Error>> {
                         spent: m.spent,
                         throughput: m.throughput,
                         latency: m.latency,
+                        fairness: f.map(|f| f.jain),
+                        min_max_ratio: f.map(|f| f.min_max_ratio),
+                        finish_spread: f.map(|f| f.finish_spread),
+                        pin: Some(placement.clone()),
+                        cpus: stats.cpus.clone(),
+                        reclaim: stats.reclaim.map(|reclaim| reclaim.time),
+                        reclaim_complete: stats.reclaim.map(|reclaim| reclaim.complete),
+                        settle: Some(stats.settle),
+                        allocator: Some(allocator.to_string()),
+                        hit_ratio: stats.hit_ratio,
+                        dep_source: stats
+                            .dep
+                            .and_then(|dep| dep.source.as_deref().map(Into::into)),
+                        dep_checksum: stats
+                            .dep
+                            .and_then(|dep| dep.checksum.as_deref().map(Into::into)),
+                        source: None,
                     })
                     .expect("cannot serialize");
                     wr.flush().expect("cannot flush");
                 }) as BenchHandler
             } else {
-                Box::new(|_: &str, n, m: &Measurement| {
-                    eprintln!(
+                Box::new(|_: &str, n, m: &Measurement, stats: &RunStats| {
+                    eprint!(
                         "total_ops={}\tthreads={}\tspent={:.1?}\tlatency={:?}\tthroughput={:.0}op/s",
                         m.total_ops, n, m.spent, m.latency, m.throughput,
                     );
+
+                    if let Some(f) = stats.fairness {
+                        eprint!(
+                            "\tfairness={:.3}\tmin/max={:.3}\tfinish_spread={:.1?}",
+                            f.jain, f.min_max_ratio, f.finish_spread,
+                        );
+                    }
+
+                    if let Some(hit_ratio) = stats.hit_ratio {
+                        eprint!("\thit_ratio={:.3}", hit_ratio);
+                    }
+
+                    if let Some(reclaim) = stats.reclaim {
+                        eprint!("\treclaim={:.1?}", reclaim.time);
+
+                        if !reclaim.complete {
+                            eprint!(" (best-effort)");
+                        }
+                    }
+
+                    eprintln!("\tsettle={:.1?}", stats.settle);
                 }) as BenchHandler
             };
 
@@ -69,33 +185,42 @@ fn main() -> Result<(), Box<dyn Error>> {
                 (@internal => $dep:ident, $ty:ty) => {
                     add_bench_case::<$ty>(
                         &dep_name_with_version!($dep),
+                        deps::DEPS.get(stringify!($dep)),
                         &skip,
                         &threads,
                         workload,
                         operations,
                         gc_sleep_ms,
+                        &pin,
+                        fairness,
                         &mut handler,
                     );
                 };
                 (@internal => $name:literal, $ty:ty) => {
                     add_bench_case::<$ty>(
                         &stringify!($name).trim_matches('"'),
+                        None,
                         &skip,
                         &threads,
                         workload,
                         operations,
                         gc_sleep_ms,
+                        &pin,
+                        fairness,
                         &mut handler,
                     );
                 };
                 (@internal => $dep:ident, $name:literal, $ty:ty) => {
                     add_bench_case::<$ty>(
                         &dep_name_with_version!($dep, $name),
+                        deps::DEPS.get(stringify!($dep)),
                         &skip,
                         &threads,
                         workload,
                         operations,
                         gc_sleep_ms,
+                        &pin,
+                        fairness,
                         &mut handler,
                     );
                 };
@@ -106,6 +231,133 @@ fn main() -> Result<(), Box<dyn Error>> {
                 };
             }
 
+            // One case per lock and shard count, e.g. "sharded-16(parking_lot)".
+            macro_rules! add_sharded_bench_cases {
+                ($hasher:ty) => {
+                    add_sharded_bench_cases!(@shards $hasher;
+                        "std" => adapters::StdRwLockShard;
+                        "parking_lot" => adapters::ParkingLotRwLockShard;
+                        "std mutex" => adapters::StdMutexShard;
+                        "parking_lot mutex" => adapters::ParkingLotMutexShard
+                    )
+                };
+                (@shards $hasher:ty; $($lock:literal => $shard_lock:ty);*) => {
+                    for &shards in &shards {
+                        with_table_parameter!(shards, SHARDS => {
+                            $(
+                                let name = format!(
+                                    "sharded-{}{}({})",
+                                    shards,
+                                    if shard_padding { "-padded" } else { "" },
+                                    $lock,
+                                );
+
+                                match shard_padding {
+                                    false => add_bench_case::<adapters::ShardedTable<u64, $hasher, $shard_lock, SHARDS>>(
+                                        &name, None, &skip, &threads, workload, operations, gc_sleep_ms, &pin, fairness, &mut handler,
+                                    ),
+                                    true => add_bench_case::<adapters::ShardedTable<u64, $hasher, adapters::Padded<$shard_lock>, SHARDS>>(
+                                        &name, None, &skip, &threads, workload, operations, gc_sleep_ms, &pin, fairness, &mut handler,
+                                    ),
+                                }
+                            )*
+                        });
+                    }
+                };
+            }
+
+            // One case per dashmap version and shard amount, e.g.
+            // "dashmap@6.0.1 - 16 shards".
+            macro_rules! add_dashmap_shard_bench_cases {
+                ($hasher:ty) => {
+                    for &shards in &dashmap_shards {
+                        with_table_parameter!(shards, SHARDS => {
+                            add_bench_case::<adapters::ShardAmountDashMapTable<u64, $hasher, SHARDS>>(
+                                &format!("{} - {} shards", dep_name_with_version!(dashmap), shards),
+                                deps::DEPS.get("dashmap"),
+                                &skip,
+                                &threads,
+                                workload,
+                                operations,
+                                gc_sleep_ms,
+                                &pin,
+                                fairness,
+                                &mut handler,
+                            );
+                            add_bench_case::<adapters::ShardAmountDashMap5Table<u64, $hasher, SHARDS>>(
+                                &format!("{} - {} shards", dep_name_with_version!(dashmap5), shards),
+                                deps::DEPS.get("dashmap5"),
+                                &skip,
+                                &threads,
+                                workload,
+                                operations,
+                                gc_sleep_ms,
+                                &pin,
+                                fairness,
+                                &mut handler,
+                            );
+                        });
+                    }
+                };
+            }
+
+            // One case per crate and guard refresh interval, e.g.
+            // "papaya@0.1.3 - guard per 64 ops".
+            macro_rules! add_guarded_bench_cases {
+                ($hasher:ty) => {
+                    for &ops in &guard_refresh {
+                        with_table_parameter!(ops, EVERY => {
+                            add_bench_case::<adapters::FlurryGuardedTable<u64, $hasher, EVERY>>(
+                                &format!("{} - guard per {} ops", dep_name_with_version!(flurry), ops),
+                                deps::DEPS.get("flurry"),
+                                &skip,
+                                &threads,
+                                workload,
+                                operations,
+                                gc_sleep_ms,
+                                &pin,
+                                fairness,
+                                &mut handler,
+                            );
+                            add_bench_case::<adapters::PapayaGuardedTable<u64, $hasher, EVERY>>(
+                                &format!("{} - guard per {} ops", dep_name_with_version!(papaya), ops),
+                                deps::DEPS.get("papaya"),
+                                &skip,
+                                &threads,
+                                workload,
+                                operations,
+                                gc_sleep_ms,
+                                &pin,
+                                fairness,
+                                &mut handler,
+                            );
+                        });
+                    }
+                };
+            }
+
+            // Only caches evict, and the maps never do.
+            if let args::WorkloadType::Cache = workload {
+                match hasher {
+                    args::HasherKind::Std => {
+                        add_bench_case! {
+                            hashlink "std lru" => adapters::StdMutexHashLinkLruTable<u64, std::hash::RandomState>;
+                            hashlink "parking_lot lru" => adapters::ParkingLotMutexHashLinkLruTable<u64, std::hash::RandomState>;
+                            scc "HashCache" => adapters::SccCacheTable<u64, std::hash::RandomState>
+                        };
+                    }
+                    args::HasherKind::AHash => {
+                        add_bench_case! {
+                            hashlink "std lru" => adapters::StdMutexHashLinkLruTable<u64, ahash::RandomState>;
+                            hashlink "parking_lot lru" => adapters::ParkingLotMutexHashLinkLruTable<u64, ahash::RandomState>;
+                            scc "HashCache" => adapters::SccCacheTable<u64, ahash::RandomState>
+                        };
+                    }
+                }
+
+                return Ok(());
+            }
+
             add_bench_case! {
                 // "std(btreemap)" => adapters::StdRwLockBTreeMapTable<u64>;
                 // "std(parking_lot, btreemap)" => adapters::ParkingLotRwLockBTreeMapTable<u64>;
@@ -120,6 +372,9 @@ fn main() -> Result<(), Box<dyn Error>> {
                         // std / parking_lot reference
                         "std" => adapters::StdRwLockStdHashMapTable<u64, std::hash::RandomState>;
                         "std(parking_lot)" => adapters::ParkingLotRwLockStdHashMapTable<u64, std::hash::RandomState>;
+                        "std(mutex)" => adapters::StdMutexStdHashMapTable<u64, std::hash::RandomState>;
+                        "std(parking_lot mutex)" => adapters::ParkingLotMutexStdHashMapTable<u64, std::hash::RandomState>;
+                        "std(spinlock)" => adapters::SpinLockStdHashMapTable<u64, std::hash::RandomState>;
 
                         // 3rd party
                         contrie => adapters::ContrieTable<u64, std::hash::RandomState>;
@@ -133,12 +388,19 @@ fn main() -> Result<(), Box<dyn Error>> {
                         scc "HashMap" => adapters::SccMapTable<u64, std::hash::RandomState>;
                         scc "HashIndex" => adapters::SccIndexTable<u64, std::hash::RandomState>
                     };
+
+                    add_dashmap_shard_bench_cases!(std::hash::RandomState);
+                    add_guarded_bench_cases!(std::hash::RandomState);
+                    add_sharded_bench_cases!(std::hash::RandomState);
                 }
                 args::HasherKind::AHash => {
                     add_bench_case! {
                         // std / parking_lot reference
                         "std" => adapters::StdRwLockStdHashMapTable<u64, ahash::RandomState>;
                         "std(parking_lot)" => adapters::ParkingLotRwLockStdHashMapTable<u64, ahash::RandomState>;
+                        "std(mutex)" => adapters::StdMutexStdHashMapTable<u64, ahash::RandomState>;
+                        "std(parking_lot mutex)" => adapters::ParkingLotMutexStdHashMapTable<u64, ahash::RandomState>;
+                        "std(spinlock)" => adapters::SpinLockStdHashMapTable<u64, ahash::RandomState>;
 
                         // 3rd party
                         contrie => adapters::ContrieTable<u64, ahash::RandomState>;
@@ -152,39 +414,255 @@ fn main() -> Result<(), Box<dyn Error>> {
                         scc "HashMap" => adapters::SccMapTable<u64, ahash::RandomState>;
                         scc "HashIndex" => adapters::SccIndexTable<u64, ahash::RandomState>
                     };
+
+                    add_dashmap_shard_bench_cases!(ahash::RandomState);
+                    add_guarded_bench_cases!(ahash::RandomState);
+                    add_sharded_bench_cases!(ahash::RandomState);
                 }
             }
         }
         args::Command::Plot {
             dir,
             name,
+            input,
+            overlay,
+            by_allocator,
+            include,
+            exclude,
+            rename,
+            group_by,
             width,
             height,
             latency_limit_ns,
+            latency_axis,
+            format,
+            kind,
+            log2_threads,
+            baseline,
+            at_threads,
+            bars_by,
+            heatmap_columns,
+            heatmap_relative,
+            dashboard,
+            theme,
+            terminal,
+        } => {
+            // Without a name nor inputs, the directory holds the results.
+            let input = match (&name, input.is_empty()) {
+                (None, true) => vec![dir.clone()],
+                _ => input,
+            };
+
+            let name = name.unwrap_or_else(|| {
+                let dir = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+                dir.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            });
+
+            let mut output = plot::Output {
+                dir,
+                name,
+                width,
+                height,
+                formats: format,
+                theme: match theme {
+                    Some(theme) => plot::Theme::load(&theme)?,
+                    None => plot::Theme::default(),
+                },
+            };
+
+            let selection = plot::Selection {
+                include,
+                exclude,
+                rename,
+            };
+
+            // Each input is plotted under its file stem.
+            let mut sources = if input.is_empty() {
+                vec![(output.name.clone(), plot::Groups::init(&selection))]
+            } else if by_allocator {
+                let (sources, allocators) = plot::compare_allocators(&input, &selection)?;
+                output
+                    .theme
+                    .assign_dashes(allocators.iter().map(String::as_str));
+                sources
+            } else {
+                plot::group_sources(plot::load_sources(&input, &selection)?, group_by)
+            };
+
+            sources.retain(|(_, groups)| !groups.is_empty());
+
+            if sources.is_empty() {
+                return Err("no series left to plot".into());
+            }
+
+            if overlay {
+                output
+                    .theme
+                    .assign_dashes(sources.iter().map(|(source, _)| source.as_str()));
+                sources = vec![(output.name.clone(), plot::Groups::overlay(sources))];
+            }
+
+            if let Some(chars) = terminal {
+                plot::print_terminal(&sources, &output, latency_limit_ns, chars)?;
+                return Ok(());
+            }
+
+            if dashboard {
+                plot::plot_dashboard(&sources, &output, latency_limit_ns)?;
+                return Ok(());
+            }
+
+            if kind.contains(&args::PlotKind::Bars) {
+                plot::plot_bars(&sources, &output, at_threads, bars_by)?;
+            }
+
+            if kind.contains(&args::PlotKind::Heatmap) {
+                plot::plot_heatmap(
+                    &sources,
+                    &output,
+                    at_threads,
+                    heatmap_columns,
+                    heatmap_relative,
+                )?;
+            }
+
+            for (source, mut groups) in sources {
+                let output = plot::Output {
+                    name: source,
+                    ..output.clone()
+                };
+
+                let baseline = match &baseline {
+                    Some(baseline)
+                        if kind.iter().any(|kind| {
+                            matches!(kind, args::PlotKind::Throughput | args::PlotKind::Latency)
+                        }) =>
+                    {
+                        Some(groups.baseline(baseline)?)
+                    }
+                    _ => None,
+                };
+
+                for kind in &kind {
+                    groups = match (kind, &baseline) {
+                        (args::PlotKind::Throughput, Some(baseline)) => {
+                            groups.plot_relative_throughput(&output, baseline)?
+                        }
+                        (args::PlotKind::Latency, Some(baseline)) => {
+                            groups.plot_relative_latency(&output, baseline)?
+                        }
+                        (args::PlotKind::Throughput, None) => groups.plot_throughput(&output)?,
+                        (args::PlotKind::Latency, None) => {
+                            groups.plot_latency(&output, latency_limit_ns, latency_axis)?
+                        }
+                        (args::PlotKind::Fairness, _) => groups.plot_fairness(&output)?,
+                        (args::PlotKind::Speedup, _) => {
+                            groups.plot_speedup(&output, log2_threads)?
+                        }
+                        (args::PlotKind::Efficiency, _) => {
+                            groups.plot_efficiency(&output, log2_threads)?
+                        }
+                        (args::PlotKind::Bars | args::PlotKind::Heatmap, _) => groups,
+                    };
+                }
+            }
+        }
+        args::Command::Report {
+            input,
+            output,
+            title,
+            theme,
         } => {
-            let dir = dir.to_string_lossy();
-            plot::Groups::init()
-                .plot_throughput(&dir, &name, width, height)?
-                .plot_latency(&dir, &name, width, height, latency_limit_ns)?;
+            let title = title.unwrap_or_else(|| {
+                let input = input[0].canonicalize().unwrap_or_else(|_| input[0].clone());
+                input
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            let theme = match theme {
+                Some(theme) => plot::Theme::load(&theme)?,
+                None => plot::Theme::default(),
+            };
+
+            report::write_report(
+                &output,
+                &title,
+                &plot::load_sources(&input, &plot::Selection::default())?,
+                &theme,
+            )?;
+        }
+        args::Command::Summarize {
+            input,
+            format,
+            readme,
+        } => {
+            let sources = plot::load_sources(&input, &plot::Selection::default())?;
+            let summary = match format {
+                args::SummaryFormat::Markdown => summary::markdown(&sources),
+            };
+
+            match readme {
+                Some(readme) => summary::update_readme(&readme, &summary)?,
+                None => print!("{}", summary),
+            }
+        }
+        args::Command::Palette { count, input } => {
+            if input.is_empty() {
+                for color in plot::palette(count) {
+                    println!("{}", plot::hex(color));
+                }
+            } else {
+                let sources = plot::load_sources(&input, &plot::Selection::default())?;
+                print!("{}", plot::palette_theme(&sources));
+            }
         }
     }
 
     Ok(())
 }
 
-type BenchHandler = Box<dyn FnMut(&str, u32, &Measurement)>;
+/// Metrics of a run collected beside bustle's [`Measurement`].
+struct RunStats<'a> {
+    /// The benchmarked crate, as resolved from `Cargo.lock`.
+    dep: Option<&'a deps::Dependency>,
+
+    fairness: Option<Fairness>,
 
+    /// The CPUs the worker threads were pinned to, see [`affinity`].
+    cpus: Option<String>,
+
+    /// Time spent tearing down the table, see [`adapters::Reclaim`].
+    reclaim: Option<reclaim::Reclamation>,
+
+    /// Time spent waiting for the memory usage to settle after the run.
+    settle: Duration,
+
+    /// The fraction of lookups finding their key, see [`cache`].
+    hit_ratio: Option<f64>,
+}
+
+type BenchHandler = Box<dyn FnMut(&str, u32, &Measurement, &RunStats<'_>)>;
+
+#[allow(clippy::too_many_arguments)]
 fn add_bench_case<C>(
     name: &str,
-    skip: &Vec<Arc<str>>,
-    threads: &Option<Vec<u32>>,
+    dep: Option<&deps::Dependency>,
+    skip: &[Arc<str>],
+    threads: &[u32],
     workload: args::WorkloadType,
     operations: f64,
     gc_sleep_ms: u64,
+    pin: &args::PinPolicy,
+    probe_fairness: bool,
     handler: &mut BenchHandler,
 ) where
-    C: bustle::Collection,
-    <C::Handle as bustle::CollectionHandle>::Key: Send + Debug,
+    C: bustle::Collection + adapters::Reclaim,
+    <C::Handle as bustle::CollectionHandle>::Key: Send + Debug + From<u64>,
 {
     if skip.iter().find(|s| name.starts_with(s.as_ref())).is_some() {
         println!("-- {} [skipped]", name);
@@ -193,41 +671,39 @@ fn add_bench_case<C>(
         println!("-- {}", name);
     }
 
-    let threads = threads.as_ref().cloned().unwrap_or_else(|| {
-        let n = num_cpus::get();
-
-        match n {
-            0..=10 => (1..=n as u32).collect(),
-            11..=16 => std::iter::once(1)
-                .chain((0..=n as u32).step_by(2).skip(1))
-                .collect(),
-            _ => std::iter::once(1)
-                .chain((0..=n as u32).step_by(4).skip(1))
-                .collect(),
-        }
-    });
-
-    #[inline]
-    fn gc_cycle(gc_sleep_ms: u64) {
-        use std::{thread::sleep, time::Duration};
+    if let Some(dep) = dep {
+        println!("   {}", dep);
+    }
 
-        sleep(Duration::from_millis(gc_sleep_ms));
+    for n in threads {
+        affinity::arm(pin, *n).expect("cannot resolve thread placement");
 
-        let mut new_guard = crossbeam_epoch::pin();
+        // Only probe on demand, as counting every operation slows the fastest
+        // maps down.
+        let (m, hit_ratio, fairness) = if probe_fairness {
+            fairness::arm(args::WorkloadType::ops_per_thread(*n, operations));
 
-        new_guard.flush();
+            let (m, hit_ratio) = workload
+                .run::<fairness::Probed<affinity::Pinned<reclaim::Reclaimed<C>>>>(*n, operations);
 
-        for _ in 0..32 {
-            new_guard.repin();
-        }
-    }
+            (m, hit_ratio, fairness::collect(*n))
+        } else {
+            let (m, hit_ratio) =
+                workload.run::<affinity::Pinned<reclaim::Reclaimed<C>>>(*n, operations);
 
-    for n in &threads {
-        let m = workload.create(*n, operations).run_silently::<C>();
+            (m, hit_ratio, None)
+        };
 
-        handler(name, *n, &m);
+        let stats = RunStats {
+            dep,
+            cpus: affinity::armed_cpus().map(|cpus| affinity::format_cpu_list(&cpus)),
+            fairness,
+            reclaim: reclaim::take(),
+            hit_ratio,
+            settle: reclaim::settle(Duration::from_millis(gc_sleep_ms)),
+        };
 
-        gc_cycle(gc_sleep_ms);
+        handler(name, *n, &m, &stats);
     }
 
     println!();
@@ -1,10 +1,108 @@
-use std::{collections::BTreeMap, error::Error, io, ops, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs, io, ops,
+    path::{Path, PathBuf},
+};
 
-use plotters::prelude::*;
+use plotters::{
+    coord::{
+        ranged1d::{AsRangedCoord, ValueFormatter},
+        Shift,
+    },
+    prelude::*,
+};
+use regex::Regex;
 
-use crate::record::Record;
+use crate::{
+    args::{AtThreads, GroupBy, ImageFormat, LatencyAxis, Rename},
+    record::Record,
+};
 
-const FONT: &str = "Fira Code";
+use self::font::FONT;
+
+pub(crate) use self::{
+    bars::plot_bars,
+    dashboard::plot_dashboard,
+    heatmap::plot_heatmap,
+    style::{hex, palette, palette_theme, Theme},
+    terminal::print_terminal,
+};
+
+use self::style::SeriesStyle;
+
+mod bars;
+mod dashboard;
+mod font;
+mod heatmap;
+mod style;
+mod terminal;
+
+/// Where and how the charts are exported.
+#[derive(Debug, Clone)]
+pub(crate) struct Output {
+    pub dir: PathBuf,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub formats: Vec<ImageFormat>,
+    pub theme: Theme,
+}
+
+impl Output {
+    /// `<dir>/<name>.<kind>.<ext>`
+    fn path(&self, kind: &str, format: ImageFormat) -> PathBuf {
+        self.dir
+            .join(format!("{}.{}.{}", self.name, kind, format.extension()))
+    }
+}
+
+/// The width of a chart fitting `caption`, drawn in the monospace font at the
+/// size of every caption.
+fn caption_width(caption: &str) -> u32 {
+    // Glyphs advance 0.6em, at the 20 / 1.24 pixels per em plotters draws a
+    // size of 20 with, within the margins of the chart.
+    caption.chars().count() as u32 * 10 + 40
+}
+
+/// Draw a chart once per requested format, binding the drawing area of the
+/// format's backend to `$root`.
+macro_rules! render {
+    ($output:expr, $kind:expr, |$root:ident| $draw:expr) => {{
+        let output: &Output = $output;
+        let size = (output.width, output.height);
+
+        font::register();
+
+        fs::create_dir_all(&output.dir)?;
+
+        for format in &output.formats {
+            let path = output.path($kind, *format);
+
+            match format {
+                ImageFormat::Svg => {
+                    let mut svg = String::new();
+
+                    {
+                        let $root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+                        $draw;
+                        $root.present()?;
+                    }
+
+                    fs::write(&path, font::outline_text(&svg))?;
+                }
+                ImageFormat::Png => {
+                    let $root =
+                        font::TextBackend::new(BitMapBackend::new(&path, size)).into_drawing_area();
+                    $draw;
+                    $root.present()?;
+                }
+            }
+        }
+    }};
+}
+
+use render;
 
 #[derive(Debug, Default)]
 #[repr(transparent)]
@@ -30,51 +128,313 @@ impl From<BTreeMap<String, Vec<Record>>> for Groups {
     }
 }
 
-const COLORS: &[RGBColor] = &[
-    RGBColor(251, 26, 26),
-    RGBColor(248, 111, 28),
-    RGBColor(248, 193, 26),
-    RGBColor(223, 252, 24),
-    RGBColor(131, 242, 20),
-    RGBColor(63, 250, 37),
-    RGBColor(30, 243, 83),
-    RGBColor(49, 251, 175),
-    RGBColor(46, 251, 251),
-    RGBColor(11, 162, 252),
-    RGBColor(48, 98, 247),
-    RGBColor(46, 16, 253),
-    RGBColor(143, 35, 251),
-    RGBColor(221, 52, 246),
-    RGBColor(246, 58, 199),
-    RGBColor(250, 9, 99),
-];
+/// Which series to read, and under which names.
+#[derive(Debug, Default)]
+pub(crate) struct Selection {
+    /// Keep the series matching any of these, or every series if empty.
+    pub include: Vec<Regex>,
+
+    /// Drop the series matching any of these.
+    pub exclude: Vec<Regex>,
+
+    pub rename: Vec<Rename>,
+}
+
+impl Selection {
+    /// The record if its series is selected, renamed. Patterns match the
+    /// names as written in the results.
+    fn apply(&self, mut record: Record) -> Option<Record> {
+        let name = &record.name;
+
+        if !self.include.is_empty() && !self.include.iter().any(|re| re.is_match(name)) {
+            return None;
+        }
+
+        if self.exclude.iter().any(|re| re.is_match(name)) {
+            return None;
+        }
+
+        if let Some(renamed) = self.rename.iter().find_map(|rename| rename.apply(name)) {
+            record.name = renamed;
+        }
+
+        Some(record)
+    }
+}
+
+/// The series of every source, by source name.
+pub(crate) type Sources = Vec<(String, Groups)>;
+
+/// Read every input, expanding directories to the `*.csv` files they hold,
+/// and name each after its file stem.
+pub(crate) fn load_sources(
+    inputs: &[PathBuf],
+    selection: &Selection,
+) -> Result<Sources, Box<dyn Error>> {
+    let mut paths = Vec::new();
+
+    for input in inputs {
+        if !input.is_dir() {
+            paths.push(input.clone());
+            continue;
+        }
+
+        let mut csvs = fs::read_dir(input)?
+            .map(|entry| Ok(entry?.path()))
+            .filter(|path| {
+                path.as_ref().map_or(true, |path| {
+                    path.extension().is_some_and(|ext| ext == "csv")
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if csvs.is_empty() {
+            return Err(format!("no CSV files in {}", input.display()).into());
+        }
+
+        csvs.sort();
+        paths.extend(csvs);
+    }
+
+    paths
+        .iter()
+        .map(|path| {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            Ok((stem.into_owned(), Groups::load(path, selection)?))
+        })
+        .collect()
+}
+
+/// Read every input as the results of one allocator, and gather the results
+/// of the same workload and hasher, telling their series apart by allocator.
+///
+/// Records name their allocator since it is recorded, older ones are
+/// assumed to come from the allocator named after their directory, e.g.
+/// `results/jemalloc`. Returns the allocators in order of appearance too.
+pub(crate) fn compare_allocators(
+    inputs: &[PathBuf],
+    selection: &Selection,
+) -> Result<(Sources, Vec<String>), Box<dyn Error>> {
+    let mut sources = Sources::new();
+    let mut allocators = Vec::new();
+
+    for input in inputs {
+        let dir = match input.is_dir() {
+            true => input.as_path(),
+            false => input.parent().unwrap_or(Path::new(".")),
+        };
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_owned());
+        let fallback = dir.file_name().unwrap_or_default().to_string_lossy();
+
+        for (source, groups) in load_sources(std::slice::from_ref(input), selection)? {
+            let index = match sources.iter().position(|(name, _)| *name == source) {
+                Some(index) => index,
+                None => {
+                    sources.push((source, Groups::default()));
+                    sources.len() - 1
+                }
+            };
+
+            for mut record in groups.0.into_values().flatten() {
+                let allocator = record
+                    .allocator
+                    .clone()
+                    .unwrap_or_else(|| fallback.clone().into_owned());
+
+                if !allocators.contains(&allocator) {
+                    allocators.push(allocator.clone());
+                }
+
+                record.source = Some(allocator);
+                sources[index].1.insert(record);
+            }
+        }
+    }
+
+    Ok((sources, allocators))
+}
+
+/// Gather the series of the sources into one set of plots per group, telling
+/// the series of a group apart by the part of the source they don't share.
+pub(crate) fn group_sources(sources: Sources, by: GroupBy) -> Sources {
+    if by == GroupBy::Source {
+        return sources;
+    }
+
+    let mut groups: Vec<(String, Vec<(String, Record)>)> = Vec::new();
+
+    for (source, records) in sources {
+        let (workload, hasher) = split_source(&source);
+        let (workload, hasher) = (workload.to_owned(), hasher.to_owned());
+
+        for record in records.0.into_values().flatten() {
+            let (group, label) = match by {
+                GroupBy::Source => unreachable!(),
+                GroupBy::Workload => (workload.clone(), hasher.clone()),
+                GroupBy::Hasher => (hasher.clone(), workload.clone()),
+                GroupBy::Family => (family(&record.name), source.clone()),
+            };
+
+            match groups.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, members)) => members.push((label, record)),
+                None => groups.push((group, vec![(label, record)])),
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, members)| {
+            let labels = members
+                .iter()
+                .map(|(label, _)| label)
+                .collect::<BTreeSet<_>>()
+                .len();
+            let mut records = Groups::default();
+
+            for (label, mut record) in members {
+                // A single label would only lengthen the names.
+                if labels > 1 {
+                    record.source = Some(label);
+                }
+
+                records.insert(record);
+            }
+
+            (group, records)
+        })
+        .collect()
+}
+
+/// Split `<workload>.<hasher>` input names.
+pub(crate) fn split_source(source: &str) -> (&str, &str) {
+    source.rsplit_once('.').unwrap_or((source, ""))
+}
+
+/// The map a series name refers to, without the version of its crate, e.g.
+/// `scc - HashMap` for `scc@2.1.16 - HashMap`.
+fn family(name: &str) -> String {
+    match name.split_once('@') {
+        Some((krate, rest)) => match rest.split_once(' ') {
+            Some((_, label)) => format!("{} {}", krate, label),
+            None => krate.to_owned(),
+        },
+        None => name.to_owned(),
+    }
+}
+
+/// The style of every series of every source.
+fn series_styles<'a>(
+    sources: &'a [(String, Groups)],
+    theme: &Theme,
+) -> BTreeMap<&'a str, SeriesStyle> {
+    sources
+        .iter()
+        .flat_map(|(_, groups)| groups.iter())
+        .map(|(name, records)| (name.as_str(), theme.series_style(records)))
+        .collect()
+}
+
+/// The top of a latency axis showing the percentile `p` of the sorted
+/// `latencies`, by nearest rank, with some room above it.
+fn percentile_top(latencies: &[f64], p: f64) -> f64 {
+    let Some(max) = latencies.last() else {
+        return 1.;
+    };
+
+    let rank = (p / 100. * latencies.len() as f64).ceil() as usize;
+    (latencies[rank.clamp(1, latencies.len()) - 1] * 1.1)
+        .min(*max)
+        .max(1.)
+}
+
+/// The legend entry of a latency series, telling how far it goes when it is
+/// clipped by the top of the axis.
+fn clipped_label(series: &str, points: &[(u32, f64)], y_max: f64) -> String {
+    match points
+        .iter()
+        .map(|(_, latency)| *latency)
+        .filter(|latency| *latency > y_max)
+        .reduce(f64::max)
+    {
+        Some(max) => format!("{} (clipped, max {:.0} ns)", series, max),
+        None => series.to_owned(),
+    }
+}
+
+fn record_at(records: &[Record], at: AtThreads) -> Option<&Record> {
+    match at {
+        AtThreads::Max => records.iter().max_by_key(|record| record.threads),
+        AtThreads::Count(n) => records.iter().find(|record| record.threads == n),
+    }
+}
 
 impl Groups {
-    pub(crate) fn init() -> Self {
+    pub(crate) fn init(selection: &Selection) -> Self {
+        Self::from_reader(io::stdin(), selection).expect("invalid record")
+    }
+
+    /// Read the results of a CSV file.
+    pub(crate) fn load(path: &Path, selection: &Selection) -> Result<Self, Box<dyn Error>> {
+        let file = fs::File::open(path)
+            .map_err(|err| format!("cannot open {}: {}", path.display(), err))?;
+
+        Self::from_reader(file, selection)
+            .map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    fn from_reader(rdr: impl io::Read, selection: &Selection) -> csv::Result<Self> {
         let mut groups = Self::default();
 
-        csv::Reader::from_reader(io::stdin())
-            .deserialize()
-            .map(|result| result.expect("invalid record"))
-            .for_each(|record: Record| {
-                let group = groups.entry(record.name.clone()).or_insert_with(Vec::new);
-                group.push(record);
-            });
+        for record in csv::Reader::from_reader(rdr).deserialize() {
+            if let Some(record) = selection.apply(record?) {
+                groups.insert(record);
+            }
+        }
+
+        Ok(groups)
+    }
+
+    /// Merge the results of several sources, naming every series after the
+    /// source it comes from.
+    pub(crate) fn overlay(sources: Vec<(String, Groups)>) -> Self {
+        let mut groups = Self::default();
+
+        for (source, records) in sources {
+            for mut record in records.0.into_values().flatten() {
+                record.source.get_or_insert_with(|| source.clone());
+                groups.insert(record);
+            }
+        }
 
         groups
     }
 
-    pub(crate) fn plot_throughput(
-        self,
-        dir: &str,
-        name: &str,
-        width: u32,
-        height: u32,
-    ) -> Result<Self, Box<dyn Error>> {
-        let path = format!("{}/{}.throughput.svg", dir, name);
+    fn insert(&mut self, record: Record) {
+        let series = match &record.source {
+            Some(source) => format!("{}: {}", source, record.name),
+            None => record.name.clone(),
+        };
+
+        self.entry(series).or_default().push(record);
+    }
+
+    pub(crate) fn plot_throughput(self, output: &Output) -> Result<Self, Box<dyn Error>> {
+        render!(output, "throughput", |root| self
+            .draw_throughput(&root, output)?);
 
-        let root = SVGBackend::new(&path, (width, height)).into_drawing_area();
+        Ok(self)
+    }
 
+    fn draw_throughput<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        output: &Output,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
         root.fill(&WHITE)?;
 
         let (x_max, y_max) = self
@@ -83,10 +443,14 @@ impl Groups {
             .map(|record| (record.threads, record.throughput))
             .fold((0, 0f64), |res, cur| (res.0.max(cur.0), res.1.max(cur.1)));
 
-        let mut chart = ChartBuilder::on(&root)
+        let mut chart = ChartBuilder::on(root)
             .margin(10)
             .caption(
-                &format!("{}: Throughput @ {}", name, env!("CARGO_PKG_VERSION")),
+                format!(
+                    "{}: Throughput @ {}",
+                    output.name,
+                    env!("CARGO_PKG_VERSION")
+                ),
                 (FONT, 20),
             )
             .set_label_area_size(LabelAreaPosition::Left, 70)
@@ -104,18 +468,16 @@ impl Groups {
             .x_desc("Threads")
             .draw()?;
 
-        let colors = COLORS.iter().cycle();
+        for (series, records) in self.iter() {
+            let points = records
+                .iter()
+                .map(|record| (record.threads, record.throughput))
+                .collect::<Vec<_>>();
 
-        for (records, color) in self.values().zip(colors) {
-            chart
-                .draw_series(LineSeries::new(
-                    records
-                        .iter()
-                        .map(|record| (record.threads, record.throughput)),
-                    color,
-                ))?
-                .label(&records[0].name)
-                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+            output
+                .theme
+                .series_style(records)
+                .draw(&mut chart, &points, series)?;
         }
 
         chart
@@ -126,43 +488,94 @@ impl Groups {
             .border_style(BLACK)
             .draw()?;
 
-        Ok(self)
+        Ok(())
     }
 
     pub(crate) fn plot_latency(
         self,
-        dir: &str,
-        name: &str,
-        width: u32,
-        height: u32,
+        output: &Output,
         latency_limit_ns: u64,
+        axis: LatencyAxis,
     ) -> Result<Self, Box<dyn Error>> {
-        let path = format!("{}/{}.latency.svg", dir, name);
+        let mut latencies = self
+            .values()
+            .flatten()
+            .map(|record| record.latency.as_nanos() as f64)
+            .collect::<Vec<_>>();
+
+        latencies.sort_by(f64::total_cmp);
 
-        let root = SVGBackend::new(&path, (width, height)).into_drawing_area();
+        let max = latencies.last().copied().unwrap_or(1.).max(1.);
 
+        match axis {
+            LatencyAxis::Limit => {
+                let y_max = max.min(latency_limit_ns as f64);
+                render!(output, "latency", |root| self.draw_latency(
+                    &root,
+                    output,
+                    0.0..y_max,
+                    y_max,
+                    20
+                )?);
+            }
+            LatencyAxis::Percentile(p) => {
+                let y_max = percentile_top(&latencies, p);
+
+                render!(output, "latency", |root| self.draw_latency(
+                    &root,
+                    output,
+                    0.0..y_max,
+                    y_max,
+                    20
+                )?);
+            }
+            LatencyAxis::Log => {
+                let min = latencies.iter().copied().find(|v| *v > 0.).unwrap_or(1.);
+                render!(output, "latency", |root| self.draw_latency(
+                    &root,
+                    output,
+                    (min / 1.2..max * 1.2).log_scale(),
+                    max * 1.2,
+                    10
+                )?);
+            }
+        }
+
+        Ok(self)
+    }
+
+    fn draw_latency<DB, Y>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        output: &Output,
+        y: Y,
+        y_max: f64,
+        y_labels: usize,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+        Y: AsRangedCoord<Value = f64>,
+        Y::CoordDescType: ValueFormatter<f64>,
+    {
         root.fill(&WHITE)?;
 
-        let (x_max, y_max) = self
+        let x_max = self
             .values()
             .flatten()
-            .map(|record| (record.threads, record.latency))
-            .fold((0, Duration::from_secs(0)), |res, cur| {
-                (res.0.max(cur.0), res.1.max(cur.1))
-            });
-
-        let y_max = latency_limit_ns.min(y_max.as_nanos() as u64);
+            .map(|record| record.threads)
+            .fold(0, u32::max);
 
-        let mut chart = ChartBuilder::on(&root)
+        let mut chart = ChartBuilder::on(root)
             .margin(10)
             .caption(
-                &format!("{}: Latency @ {}", name, env!("CARGO_PKG_VERSION")),
+                format!("{}: Latency @ {}", output.name, env!("CARGO_PKG_VERSION")),
                 (FONT, 20),
             )
             .set_label_area_size(LabelAreaPosition::Left, 70)
             .set_label_area_size(LabelAreaPosition::Right, 70)
             .set_label_area_size(LabelAreaPosition::Bottom, 40)
-            .build_cartesian_2d(1..x_max, 0..y_max)?;
+            .build_cartesian_2d(1..x_max, y)?;
 
         chart
             .configure_mesh()
@@ -170,23 +583,32 @@ impl Groups {
             .x_label_formatter(&|v| format!("{}", v))
             .y_label_formatter(&|v| format!("{:.0} ns", v))
             .x_labels(20)
-            .y_labels(20)
+            .y_labels(y_labels)
             .y_desc("Latency")
             .x_desc("Threads")
             .draw()?;
 
-        let colors = COLORS.iter().cycle();
+        for (series, records) in self.iter() {
+            let points = records
+                .iter()
+                .map(|record| (record.threads, record.latency.as_nanos() as f64))
+                .collect::<Vec<_>>();
+
+            let above = points
+                .iter()
+                .filter(|(_, latency)| *latency > y_max)
+                .collect::<Vec<_>>();
 
-        for (records, color) in self.values().zip(colors) {
-            chart
-                .draw_series(LineSeries::new(
-                    records
-                        .iter()
-                        .map(|record| (record.threads, record.latency.as_nanos() as u64)),
-                    color,
-                ))?
-                .label(&records[0].name)
-                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+            let label = clipped_label(series, &points, y_max);
+
+            let style = output.theme.series_style(records);
+            style.draw(&mut chart, &points, &label)?;
+
+            // Mark where the series leaves the chart, right below its top.
+            chart.draw_series(above.iter().map(|(threads, _)| {
+                EmptyElement::at((*threads, y_max))
+                    + TriangleMarker::new((0, 6), 5, style.color.filled())
+            }))?;
         }
 
         chart
@@ -197,6 +619,742 @@ impl Groups {
             .border_style(BLACK)
             .draw()?;
 
+        Ok(())
+    }
+
+    pub(crate) fn plot_fairness(self, output: &Output) -> Result<Self, Box<dyn Error>> {
+        // Results from older versions don't carry fairness metrics.
+        if self
+            .values()
+            .flatten()
+            .all(|record| record.fairness.is_none())
+        {
+            return Ok(self);
+        }
+
+        render!(output, "fairness", |root| self
+            .draw_fairness(&root, output)?);
+
+        Ok(self)
+    }
+
+    fn draw_fairness<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        output: &Output,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+
+        let x_max = self
+            .values()
+            .flatten()
+            .map(|record| record.threads)
+            .fold(0, |res, cur| res.max(cur));
+
+        let mut chart = ChartBuilder::on(root)
+            .margin(10)
+            .caption(
+                format!("{}: Fairness @ {}", output.name, env!("CARGO_PKG_VERSION")),
+                (FONT, 20),
+            )
+            .set_label_area_size(LabelAreaPosition::Left, 70)
+            .set_label_area_size(LabelAreaPosition::Right, 70)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .build_cartesian_2d(1..x_max, 0.0..1.0)?;
+
+        chart
+            .configure_mesh()
+            .disable_y_mesh()
+            .x_label_formatter(&|v| format!("{}", v))
+            .y_label_formatter(&|v| format!("{:.2}", v))
+            .x_labels(20)
+            .y_labels(10)
+            .y_desc("Jain's fairness index")
+            .x_desc("Threads")
+            .draw()?;
+
+        for (series, records) in self.iter() {
+            let points = records
+                .iter()
+                .filter_map(|record| Some((record.threads, record.fairness?)))
+                .collect::<Vec<_>>();
+
+            output
+                .theme
+                .series_style(records)
+                .draw(&mut chart, &points, series)?;
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::LowerLeft)
+            .label_font((FONT, 13))
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        Ok(())
+    }
+
+    pub(crate) fn plot_speedup(
+        self,
+        output: &Output,
+        log2_threads: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        self.plot_scaling(output, Scaling::Speedup, log2_threads)
+    }
+
+    pub(crate) fn plot_efficiency(
+        self,
+        output: &Output,
+        log2_threads: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        self.plot_scaling(output, Scaling::Efficiency, log2_threads)
+    }
+
+    fn plot_scaling(
+        self,
+        output: &Output,
+        scaling: Scaling,
+        log2_threads: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let x_max = self
+            .values()
+            .flatten()
+            .map(|record| record.threads)
+            .fold(1, |res, cur| res.max(cur));
+
+        if log2_threads {
+            render!(output, scaling.kind(), |root| self.draw_scaling(
+                &root,
+                output,
+                scaling,
+                (1..x_max).log_scale().base(2.0)
+            )?);
+        } else {
+            render!(output, scaling.kind(), |root| self.draw_scaling(
+                &root,
+                output,
+                scaling,
+                1..x_max
+            )?);
+        }
+
         Ok(self)
     }
+
+    fn draw_scaling<DB, X>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        output: &Output,
+        scaling: Scaling,
+        x: X,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+        X: AsRangedCoord<Value = u32>,
+        X::CoordDescType: ValueFormatter<u32>,
+    {
+        root.fill(&WHITE)?;
+
+        let series = self
+            .values()
+            .map(|records| scaling.points(records))
+            .collect::<Vec<_>>();
+
+        let x: X::CoordDescType = x.into();
+        let x_max = x.range().end;
+
+        let y_max = series
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|(_, value)| *value)
+            .fold(scaling.ideal(x_max), f64::max);
+
+        let mut chart = ChartBuilder::on(root)
+            .margin(10)
+            .caption(
+                format!(
+                    "{}: {} @ {}",
+                    output.name,
+                    scaling.title(),
+                    env!("CARGO_PKG_VERSION")
+                ),
+                (FONT, 20),
+            )
+            .set_label_area_size(LabelAreaPosition::Left, 70)
+            .set_label_area_size(LabelAreaPosition::Right, 70)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .build_cartesian_2d(x, 0.0..y_max * 1.05)?;
+
+        chart
+            .configure_mesh()
+            .disable_y_mesh()
+            .x_label_formatter(&|v| format!("{}", v))
+            .y_label_formatter(&|v| scaling.format(*v))
+            .x_labels(20)
+            .y_labels(10)
+            .y_desc(scaling.title())
+            .x_desc("Threads")
+            .draw()?;
+
+        // The log scale bends the ideal line, so it is drawn through every
+        // thread count.
+        chart
+            .draw_series(DashedLineSeries::new(
+                (1..=x_max).map(|threads| (threads, scaling.ideal(threads))),
+                6,
+                4,
+                BLACK.into(),
+            ))?
+            .label("ideal")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK));
+
+        for ((name, records), series) in self.iter().zip(&series) {
+            let Some(series) = series else {
+                continue;
+            };
+
+            output
+                .theme
+                .series_style(records)
+                .draw(&mut chart, series, name)?;
+        }
+
+        chart
+            .configure_series_labels()
+            .position(scaling.legend_position())
+            .label_font((FONT, 13))
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        Ok(())
+    }
+
+    pub(crate) fn plot_relative_throughput(
+        self,
+        output: &Output,
+        baseline: &Baseline,
+    ) -> Result<Self, Box<dyn Error>> {
+        self.plot_relative(output, Relative::Throughput, baseline)
+    }
+
+    pub(crate) fn plot_relative_latency(
+        self,
+        output: &Output,
+        baseline: &Baseline,
+    ) -> Result<Self, Box<dyn Error>> {
+        self.plot_relative(output, Relative::Latency, baseline)
+    }
+
+    fn plot_relative(
+        self,
+        output: &Output,
+        relative: Relative,
+        baseline: &Baseline,
+    ) -> Result<Self, Box<dyn Error>> {
+        render!(output, relative.kind(), |root| self
+            .draw_relative(&root, output, relative, baseline)?);
+
+        Ok(self)
+    }
+
+    /// Resolve the series named `baseline` exactly, or else the only series
+    /// starting with it, warning about the thread counts other series can't
+    /// be compared at.
+    ///
+    /// Series are matched by their name without their source, and every
+    /// source has its own baseline, so overlaid sources are each compared to
+    /// the baseline they ran with.
+    pub(crate) fn baseline(&self, baseline: &str) -> Result<Baseline, Box<dyn Error>> {
+        let mut matches = BTreeMap::<_, Vec<_>>::new();
+
+        for (series, records) in self.iter() {
+            let Some(record) = records.first() else {
+                continue;
+            };
+
+            if record.name.starts_with(baseline) {
+                matches
+                    .entry(record.source.as_deref())
+                    .or_default()
+                    .push((series.as_str(), record.name.as_str()));
+            }
+        }
+
+        if matches.is_empty() {
+            return Err(format!("no series matches the baseline '{}'", baseline).into());
+        }
+
+        let mut names = BTreeSet::new();
+        let mut series = BTreeMap::new();
+
+        for (source, matches) in matches {
+            let (key, name) = match matches[..] {
+                [found] => found,
+                _ => matches
+                    .iter()
+                    .copied()
+                    .find(|(_, name)| *name == baseline)
+                    .ok_or_else(|| {
+                        format!(
+                            "the baseline '{}' matches several series: {}",
+                            baseline,
+                            matches
+                                .iter()
+                                .map(|(series, _)| *series)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    })?,
+            };
+
+            names.insert(name);
+            series.insert(source.map(String::from), key.to_owned());
+        }
+
+        let baseline = Baseline {
+            name: names.into_iter().collect::<Vec<_>>().join(", "),
+            series,
+        };
+
+        // Thread counts the baseline was not run with have nothing to be
+        // compared to, so they are left out.
+        let missing = self
+            .iter()
+            .filter(|(name, _)| !baseline.is(name))
+            .filter_map(|(name, records)| {
+                let compared = self.relative_points(&baseline, records, Relative::Throughput);
+                let missing = records.len() - compared.len();
+
+                (missing > 0).then(|| format!("{} ({} of {})", name, missing, records.len()))
+            })
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            eprintln!(
+                "thread counts without a '{}' run are left out: {}",
+                baseline.name,
+                missing.join(", ")
+            );
+        }
+
+        Ok(baseline)
+    }
+
+    /// The ratio of every record of a series to the record of its baseline
+    /// with the same thread count, if any.
+    fn relative_points(
+        &self,
+        baseline: &Baseline,
+        records: &[Record],
+        relative: Relative,
+    ) -> Vec<(u32, f64)> {
+        let Some(reference) = baseline.of(records).and_then(|series| self.get(series)) else {
+            return Vec::new();
+        };
+
+        records
+            .iter()
+            .filter_map(|record| {
+                let reference = reference
+                    .iter()
+                    .find(|reference| reference.threads == record.threads)?;
+
+                Some((
+                    record.threads,
+                    relative.value(record) / relative.value(reference),
+                ))
+            })
+            .collect()
+    }
+
+    fn draw_relative<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        output: &Output,
+        relative: Relative,
+        baseline: &Baseline,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+
+        let reference = baseline
+            .series
+            .values()
+            .filter_map(|series| self.get(series))
+            .flatten()
+            .map(|record| record.threads)
+            .collect::<BTreeSet<_>>();
+
+        let series = self
+            .iter()
+            .filter(|(name, _)| !baseline.is(name))
+            .map(|(name, records)| {
+                let points = self.relative_points(baseline, records, relative);
+                (name, records, points)
+            })
+            .collect::<Vec<_>>();
+
+        let x_max = self
+            .values()
+            .flatten()
+            .map(|record| record.threads)
+            .fold(0, |res, cur| res.max(cur));
+
+        let y_max = series
+            .iter()
+            .flat_map(|(_, _, points)| points)
+            .map(|(_, ratio)| *ratio)
+            .fold(1.0, f64::max);
+
+        let mut chart = ChartBuilder::on(root)
+            .margin(10)
+            .caption(
+                format!(
+                    "{}: {} vs {} @ {}",
+                    output.name,
+                    relative.title(),
+                    baseline.name,
+                    env!("CARGO_PKG_VERSION")
+                ),
+                (FONT, 20),
+            )
+            .set_label_area_size(LabelAreaPosition::Left, 70)
+            .set_label_area_size(LabelAreaPosition::Right, 70)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .build_cartesian_2d(1..x_max, 0.0..y_max * 1.05)?;
+
+        chart
+            .configure_mesh()
+            .disable_y_mesh()
+            .x_label_formatter(&|v| format!("{}", v))
+            .y_label_formatter(&|v| format!("{:.2}x", v))
+            .x_labels(20)
+            .y_labels(10)
+            .y_desc(format!("{} / {}", relative.title(), baseline.name))
+            .x_desc("Threads")
+            .draw()?;
+
+        chart
+            .draw_series(DashedLineSeries::new(
+                reference.iter().map(|threads| (*threads, 1.0)),
+                6,
+                4,
+                BLACK.into(),
+            ))?
+            .label(&baseline.name)
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK));
+
+        for (name, records, points) in series {
+            output
+                .theme
+                .series_style(records)
+                .draw(&mut chart, &points, name)?;
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .label_font((FONT, 13))
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        Ok(())
+    }
+}
+
+/// The series other series are plotted relative to, see `--baseline`.
+#[derive(Debug)]
+pub(crate) struct Baseline {
+    /// The name of the baseline series, without their source.
+    name: String,
+    /// The baseline series of every source, by source.
+    series: BTreeMap<Option<String>, String>,
+}
+
+impl Baseline {
+    fn is(&self, series: &str) -> bool {
+        self.series.values().any(|baseline| baseline == series)
+    }
+
+    /// The baseline of the source of a series.
+    fn of(&self, records: &[Record]) -> Option<&str> {
+        let source = records.first()?.source.clone();
+        self.series.get(&source).map(String::as_str)
+    }
+}
+
+/// How the throughput of a series scales with the number of threads.
+#[derive(Debug, Clone, Copy)]
+enum Scaling {
+    /// Throughput divided by the single-threaded throughput.
+    Speedup,
+
+    /// Speedup divided by the number of threads.
+    Efficiency,
+}
+
+impl Scaling {
+    fn kind(self) -> &'static str {
+        match self {
+            Self::Speedup => "speedup",
+            Self::Efficiency => "efficiency",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Speedup => "Speedup",
+            Self::Efficiency => "Parallel efficiency",
+        }
+    }
+
+    fn value(self, speedup: f64, threads: u32) -> f64 {
+        match self {
+            Self::Speedup => speedup,
+            Self::Efficiency => speedup / threads as f64,
+        }
+    }
+
+    /// The value of every record of a series by its thread count, none for
+    /// series without a single-threaded run to be relative to.
+    fn points(self, records: &[Record]) -> Option<Vec<(u32, f64)>> {
+        let base = records.iter().find(|record| record.threads == 1)?;
+
+        Some(
+            records
+                .iter()
+                .map(|record| {
+                    let speedup = record.throughput / base.throughput;
+                    (record.threads, self.value(speedup, record.threads))
+                })
+                .collect(),
+        )
+    }
+
+    /// The value of a series scaling linearly.
+    fn ideal(self, threads: u32) -> f64 {
+        self.value(threads as f64, threads)
+    }
+
+    fn format(self, value: f64) -> String {
+        match self {
+            Self::Speedup => format!("{:.1}x", value),
+            Self::Efficiency => format!("{:.0}%", value * 100.),
+        }
+    }
+
+    fn legend_position(self) -> SeriesLabelPosition {
+        match self {
+            Self::Speedup => SeriesLabelPosition::UpperLeft,
+            Self::Efficiency => SeriesLabelPosition::LowerLeft,
+        }
+    }
+}
+
+/// A metric plotted as a ratio to a baseline series.
+#[derive(Debug, Clone, Copy)]
+enum Relative {
+    Throughput,
+    Latency,
+}
+
+impl Relative {
+    fn kind(self) -> &'static str {
+        match self {
+            Self::Throughput => "relative-throughput",
+            Self::Latency => "relative-latency",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Throughput => "Throughput",
+            Self::Latency => "Latency",
+        }
+    }
+
+    fn value(self, record: &Record) -> f64 {
+        match self {
+            Self::Throughput => record.throughput,
+            Self::Latency => record.latency.as_nanos() as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(threads: u32, throughput: f64) -> Record {
+        Record {
+            threads,
+            throughput,
+            ..Default::default()
+        }
+    }
+
+    fn series(name: &str, runs: &[(u32, f64)]) -> Vec<Record> {
+        runs.iter()
+            .map(|(threads, throughput)| Record {
+                name: name.into(),
+                ..record(*threads, *throughput)
+            })
+            .collect()
+    }
+
+    fn groups(series: impl IntoIterator<Item = Vec<Record>>) -> Groups {
+        let mut groups = Groups::default();
+
+        for record in series.into_iter().flatten() {
+            groups.insert(record);
+        }
+
+        groups
+    }
+
+    #[test]
+    fn speedup_and_efficiency() {
+        let records = [record(1, 10.0), record(2, 15.0), record(4, 40.0)];
+
+        assert_eq!(
+            Scaling::Speedup.points(&records).unwrap(),
+            [(1, 1.0), (2, 1.5), (4, 4.0)]
+        );
+        assert_eq!(
+            Scaling::Efficiency.points(&records).unwrap(),
+            [(1, 1.0), (2, 0.75), (4, 1.0)]
+        );
+    }
+
+    #[test]
+    fn scaling_needs_a_single_threaded_run() {
+        let records = [record(2, 15.0), record(4, 40.0)];
+
+        assert!(Scaling::Speedup.points(&records).is_none());
+        assert!(Scaling::Efficiency.points(&records).is_none());
+    }
+
+    #[test]
+    fn ideal_scaling() {
+        assert_eq!(Scaling::Speedup.ideal(8), 8.0);
+        assert_eq!(Scaling::Efficiency.ideal(8), 1.0);
+        assert_eq!(Scaling::Speedup.format(2.26), "2.3x");
+        assert_eq!(Scaling::Efficiency.format(0.755), "76%");
+    }
+
+    #[test]
+    fn baseline_by_name_or_prefix() {
+        let groups = groups([
+            series("std", &[(1, 1.0)]),
+            series("std(parking_lot)", &[(1, 1.0)]),
+            series("dashmap", &[(1, 1.0)]),
+            series("dashmap5", &[(1, 1.0)]),
+        ]);
+
+        let baseline = |name| groups.baseline(name).map(|baseline| baseline.name);
+
+        assert_eq!(baseline("std").unwrap(), "std");
+        assert_eq!(baseline("std(").unwrap(), "std(parking_lot)");
+        assert_eq!(baseline("dashmap").unwrap(), "dashmap");
+        assert!(baseline("dash").is_err());
+        assert!(baseline("papaya").is_err());
+    }
+
+    #[test]
+    fn overlaid_baselines_match_without_the_source() {
+        let source = |source: &str, groups: Groups| (source.to_owned(), groups);
+        let groups = Groups::overlay(vec![
+            source(
+                "a",
+                groups([series("std", &[(1, 2.0)]), series("papaya", &[(1, 4.0)])]),
+            ),
+            source(
+                "b",
+                groups([series("std", &[(1, 4.0)]), series("papaya", &[(1, 4.0)])]),
+            ),
+        ]);
+
+        let baseline = groups.baseline("std").unwrap();
+
+        assert_eq!(baseline.name, "std");
+        assert!(baseline.is("a: std") && baseline.is("b: std"));
+
+        // Every source is compared to its own baseline.
+        let points =
+            |series| groups.relative_points(&baseline, &groups[series], Relative::Throughput);
+
+        assert_eq!(points("a: papaya"), [(1, 2.0)]);
+        assert_eq!(points("b: papaya"), [(1, 1.0)]);
+    }
+
+    #[test]
+    fn relative_points_skip_missing_thread_counts() {
+        let groups = groups([
+            series("std", &[(1, 2.0), (4, 4.0)]),
+            series("papaya", &[(1, 4.0), (2, 6.0), (4, 2.0)]),
+        ]);
+
+        let baseline = groups.baseline("std").unwrap();
+
+        assert_eq!(
+            groups.relative_points(&baseline, &groups["papaya"], Relative::Throughput),
+            [(1, 2.0), (4, 0.5)]
+        );
+    }
+
+    #[test]
+    fn selection_filters_before_renaming() {
+        let selection = Selection {
+            include: vec!["^dashmap".parse().unwrap()],
+            exclude: vec!["@6".parse().unwrap()],
+            rename: vec!["dashmap5=dashmap".parse().unwrap()],
+        };
+
+        let apply = |name: &str| {
+            selection
+                .apply(series(name, &[(1, 1.0)]).remove(0))
+                .map(|record| record.name)
+        };
+
+        assert_eq!(apply("dashmap5@5.5.3").as_deref(), Some("dashmap@5.5.3"));
+        assert_eq!(apply("dashmap@6.0.1"), None);
+        assert_eq!(apply("std"), None);
+    }
+
+    #[test]
+    fn percentile_axis_tops() {
+        let latencies = (1..=10).map(|i| i as f64 * 100.).collect::<Vec<_>>();
+        let top = |p| percentile_top(&latencies, p).round();
+
+        assert_eq!(top(50.), 550.);
+        assert_eq!(top(90.), 990.);
+        // Never above the highest latency, nor below the first rank.
+        assert_eq!(top(100.), 1000.);
+        assert_eq!(top(0.), 110.);
+        assert_eq!(percentile_top(&[], 90.), 1.);
+    }
+
+    #[test]
+    fn clipped_series_labels() {
+        let points = [(1, 100.), (2, 2500.), (4, 9000.)];
+
+        assert_eq!(
+            clipped_label("std", &points, 2000.),
+            "std (clipped, max 9000 ns)"
+        );
+        assert_eq!(clipped_label("std", &points, 9000.), "std");
+    }
 }
@@ -0,0 +1,223 @@
+//! Bar chart summaries of several inputs at a single thread count.
+//!
+//! Line charts of many maps overlap where it matters most, so the bar chart
+//! compares the throughput of every map at one thread count, sorted, with the
+//! value written next to each bar.
+
+use std::{error::Error, fs};
+
+use plotters::{
+    coord::Shift,
+    prelude::*,
+    style::text_anchor::{HPos, Pos, VPos},
+};
+
+use super::{
+    caption_width, font, record_at, render, series_styles, split_source, Groups, Output, FONT,
+};
+use crate::args::{AtThreads, BarGroup, ImageFormat};
+
+/// The height of a bar or of a group title, in pixels.
+const ROW_HEIGHT: u32 = 20;
+
+struct Bar {
+    label: String,
+    throughput: f64,
+    color: RGBColor,
+}
+
+struct Block {
+    title: String,
+    bars: Vec<Bar>,
+}
+
+/// Plot the throughput of every map of every source at `at` threads, as one
+/// block of bars per group of sources.
+pub(crate) fn plot_bars(
+    sources: &[(String, Groups)],
+    output: &Output,
+    at: AtThreads,
+    by: BarGroup,
+) -> Result<(), Box<dyn Error>> {
+    let blocks = blocks(sources, output, at, by);
+
+    if blocks.iter().all(|block| block.bars.is_empty()) {
+        return Err(format!("no results at {}", at).into());
+    }
+
+    let rows = blocks
+        .iter()
+        .map(|block| 1 + block.bars.len())
+        .sum::<usize>() as u32;
+
+    let caption = format!(
+        "{}: Throughput at {} @ {}",
+        output.name,
+        at,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    // Grow the chart rather than squeezing the bars or clipping the caption.
+    let output = Output {
+        width: output.width.max(caption_width(&caption)),
+        height: output.height.max(rows * ROW_HEIGHT + 100),
+        ..output.clone()
+    };
+
+    render!(&output, "bars", |root| draw_bars(&root, &caption, &blocks)?);
+
+    Ok(())
+}
+
+fn blocks(
+    sources: &[(String, Groups)],
+    output: &Output,
+    at: AtThreads,
+    by: BarGroup,
+) -> Vec<Block> {
+    // Every map keeps its color across blocks.
+    let styles = &series_styles(sources, &output.theme);
+
+    let mut blocks: Vec<(String, Vec<&(String, Groups)>)> = Vec::new();
+
+    for source in sources {
+        let (workload, hasher) = split_source(&source.0);
+        let key = match by {
+            BarGroup::Source => &source.0,
+            BarGroup::Workload => workload,
+            BarGroup::Hasher => hasher,
+        };
+
+        match blocks.iter_mut().find(|(title, _)| title == key) {
+            Some((_, members)) => members.push(source),
+            None => blocks.push((key.to_owned(), vec![source])),
+        }
+    }
+
+    blocks
+        .into_iter()
+        .map(|(title, members)| {
+            let mut bars = members
+                .iter()
+                .flat_map(|(source, groups)| {
+                    let (workload, hasher) = split_source(source);
+
+                    // Sources sharing a block are told apart by what they
+                    // don't have in common.
+                    let suffix = match (by, members.len()) {
+                        (_, 1) | (BarGroup::Source, _) => String::new(),
+                        (BarGroup::Workload, _) => format!(" ({})", hasher),
+                        (BarGroup::Hasher, _) => format!(" ({})", workload),
+                    };
+
+                    groups.iter().filter_map(move |(map, records)| {
+                        Some(Bar {
+                            label: format!("{}{}", map, suffix),
+                            throughput: record_at(records, at)?.throughput,
+                            color: styles[map.as_str()].color,
+                        })
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            bars.sort_by(|a, b| b.throughput.total_cmp(&a.throughput));
+
+            Block { title, bars }
+        })
+        .collect()
+}
+
+fn draw_bars<DB>(
+    root: &DrawingArea<DB, Shift>,
+    caption: &str,
+    blocks: &[Block],
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let x_max = blocks
+        .iter()
+        .flat_map(|block| &block.bars)
+        .map(|bar| bar.throughput)
+        .fold(0f64, f64::max);
+
+    let rows = blocks
+        .iter()
+        .map(|block| 1 + block.bars.len())
+        .sum::<usize>();
+
+    // The labels are drawn left of the bars, in a monospace font.
+    let label_width = blocks
+        .iter()
+        .flat_map(|block| &block.bars)
+        .map(|bar| bar.label.chars().count())
+        .max()
+        .unwrap_or_default() as u32
+        * 8
+        + 10;
+
+    // Leave room for the value labels on the right of the longest bar.
+    let mut chart = ChartBuilder::on(root)
+        .margin(10)
+        .caption(caption, (FONT, 20))
+        .set_label_area_size(LabelAreaPosition::Left, label_width)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .build_cartesian_2d(0.0..x_max * 1.3, 0.0..rows as f64)?;
+
+    chart
+        .configure_mesh()
+        .disable_y_mesh()
+        .disable_y_axis()
+        .x_label_formatter(&|v| format!("{:.0}", v / 1_000_000.))
+        .x_labels(6)
+        .x_desc("Throughput (Mop/s)")
+        .draw()?;
+
+    let name = TextStyle::from((FONT, 13).into_font()).pos(Pos::new(HPos::Right, VPos::Center));
+    let label = TextStyle::from((FONT, 13).into_font()).pos(Pos::new(HPos::Left, VPos::Center));
+    let title = TextStyle::from((FONT, 15).into_font()).pos(Pos::new(HPos::Left, VPos::Center));
+
+    // Rows are laid out top to bottom, the first one at the top of the chart.
+    let mut row = rows as f64;
+
+    for block in blocks {
+        row -= 1.0;
+
+        chart.draw_series(std::iter::once(
+            EmptyElement::at((0.0, row + 0.5))
+                + Text::new(
+                    block.title.clone(),
+                    (-(label_width as i32), 0),
+                    title.clone(),
+                ),
+        ))?;
+
+        for bar in &block.bars {
+            row -= 1.0;
+
+            chart.draw_series(std::iter::once(Rectangle::new(
+                [(0.0, row + 0.1), (bar.throughput, row + 0.9)],
+                bar.color.mix(0.5).filled(),
+            )))?;
+
+            chart.draw_series(std::iter::once(
+                EmptyElement::at((0.0, row + 0.5))
+                    + Text::new(bar.label.clone(), (-4, 0), name.clone()),
+            ))?;
+
+            chart.draw_series(std::iter::once(
+                EmptyElement::at((bar.throughput, row + 0.5))
+                    + Text::new(
+                        format!("{:.1} Mop/s", bar.throughput / 1_000_000.),
+                        (4, 0),
+                        label.clone(),
+                    ),
+            ))?;
+        }
+    }
+
+    Ok(())
+}
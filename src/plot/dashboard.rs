@@ -0,0 +1,229 @@
+//! A single figure with the throughput and latency of every input.
+//!
+//! Panels are laid out with one row per workload and one column per hasher
+//! and metric. Panels of the same metric share their axes, and every map has
+//! the same color in every panel, so a single legend serves them all.
+
+use std::{collections::BTreeMap, error::Error, fs};
+
+use plotters::{
+    coord::Shift,
+    prelude::*,
+    style::text_anchor::{HPos, Pos, VPos},
+};
+
+use super::{font, render, series_styles, split_source, Groups, Output, SeriesStyle, FONT};
+use crate::{args::ImageFormat, record::Record};
+
+/// The width of the legend on the right of the panels, in pixels.
+const LEGEND_WIDTH: u32 = 280;
+
+#[derive(Debug, Clone, Copy)]
+enum Metric {
+    Throughput,
+    Latency,
+}
+
+impl Metric {
+    fn title(self) -> &'static str {
+        match self {
+            Self::Throughput => "Throughput",
+            Self::Latency => "Latency",
+        }
+    }
+
+    fn value(self, record: &Record) -> f64 {
+        match self {
+            Self::Throughput => record.throughput / 1_000_000.,
+            Self::Latency => record.latency.as_nanos() as f64,
+        }
+    }
+
+    fn format(self, value: f64) -> String {
+        match self {
+            Self::Throughput => format!("{:.0} Mop/s", value),
+            Self::Latency => format!("{:.0} ns", value),
+        }
+    }
+}
+
+struct Dashboard<'a> {
+    workloads: Vec<&'a str>,
+    hashers: Vec<&'a str>,
+    panels: BTreeMap<(&'a str, &'a str), &'a Groups>,
+    styles: BTreeMap<&'a str, SeriesStyle>,
+    x_max: u32,
+    throughput_max: f64,
+    latency_max: f64,
+}
+
+/// Plot every source into a grid of panels, each panel of `output`'s size.
+pub(crate) fn plot_dashboard(
+    sources: &[(String, Groups)],
+    output: &Output,
+    latency_limit_ns: u64,
+) -> Result<(), Box<dyn Error>> {
+    let mut workloads = Vec::new();
+    let mut hashers = Vec::new();
+    let mut panels = BTreeMap::new();
+
+    for (source, groups) in sources {
+        let (workload, hasher) = split_source(source);
+
+        if !workloads.contains(&workload) {
+            workloads.push(workload);
+        }
+
+        if !hashers.contains(&hasher) {
+            hashers.push(hasher);
+        }
+
+        panels.insert((workload, hasher), groups);
+    }
+
+    let records = || {
+        sources
+            .iter()
+            .flat_map(|(_, groups)| groups.values().flatten())
+    };
+
+    let dashboard = Dashboard {
+        x_max: records().map(|record| record.threads).max().unwrap_or(1),
+        throughput_max: records()
+            .map(|record| Metric::Throughput.value(record))
+            .fold(0f64, f64::max),
+        latency_max: records()
+            .map(|record| Metric::Latency.value(record))
+            .fold(0f64, f64::max)
+            .min(latency_limit_ns as f64),
+        styles: series_styles(sources, &output.theme),
+        workloads,
+        hashers,
+        panels,
+    };
+
+    let output = Output {
+        width: output.width * dashboard.hashers.len() as u32 * 2 + LEGEND_WIDTH,
+        height: output.height * dashboard.workloads.len() as u32 + 40,
+        ..output.clone()
+    };
+
+    render!(&output, "dashboard", |root| dashboard
+        .draw(&root, &output.name)?);
+
+    Ok(())
+}
+
+impl Dashboard<'_> {
+    fn draw<DB>(&self, root: &DrawingArea<DB, Shift>, name: &str) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+
+        let root = root.titled(
+            &format!("{}: Dashboard @ {}", name, env!("CARGO_PKG_VERSION")),
+            (FONT, 24),
+        )?;
+
+        let (width, _) = root.dim_in_pixel();
+        let (grid, legend) = root.split_horizontally(width - LEGEND_WIDTH);
+
+        let columns = self
+            .hashers
+            .iter()
+            .flat_map(|hasher| [(*hasher, Metric::Throughput), (*hasher, Metric::Latency)])
+            .collect::<Vec<_>>();
+
+        let panels = grid.split_evenly((self.workloads.len(), columns.len()));
+
+        for (area, (workload, (hasher, metric))) in panels.iter().zip(
+            self.workloads
+                .iter()
+                .flat_map(|workload| columns.iter().map(move |column| (*workload, *column))),
+        ) {
+            match self.panels.get(&(workload, hasher)) {
+                Some(groups) => self.draw_panel(area, workload, hasher, metric, groups)?,
+                None => {
+                    area.draw(&Text::new(
+                        format!("no results for {}.{}", workload, hasher),
+                        (10, 10),
+                        (FONT, 13),
+                    ))?;
+                }
+            }
+        }
+
+        self.draw_legend(&legend)?;
+
+        Ok(())
+    }
+
+    fn draw_panel<DB>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        workload: &str,
+        hasher: &str,
+        metric: Metric,
+        groups: &Groups,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let y_max = match metric {
+            Metric::Throughput => self.throughput_max,
+            Metric::Latency => self.latency_max,
+        };
+
+        let mut chart = ChartBuilder::on(area)
+            .margin(10)
+            .caption(
+                format!("{} ({}): {}", workload, hasher, metric.title()),
+                (FONT, 16),
+            )
+            .set_label_area_size(LabelAreaPosition::Left, 70)
+            .set_label_area_size(LabelAreaPosition::Bottom, 40)
+            .build_cartesian_2d(1..self.x_max, 0.0..y_max)?;
+
+        chart
+            .configure_mesh()
+            .disable_y_mesh()
+            .x_label_formatter(&|v| format!("{}", v))
+            .y_label_formatter(&|v| metric.format(*v))
+            .x_labels(10)
+            .y_labels(10)
+            .x_desc("Threads")
+            .draw()?;
+
+        for (name, records) in groups.iter() {
+            let points = records
+                .iter()
+                .map(|record| (record.threads, metric.value(record)))
+                .collect::<Vec<_>>();
+
+            // The shared legend is drawn apart, so the label is never shown.
+            self.styles[name.as_str()].draw(&mut chart, &points, name)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_legend<DB>(&self, area: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let style = TextStyle::from((FONT, 13).into_font()).pos(Pos::new(HPos::Left, VPos::Center));
+
+        for (i, (name, series)) in self.styles.iter().enumerate() {
+            let y = 30 + i as i32 * 20;
+
+            series.draw_legend(area, (20, y))?;
+            area.draw(&Text::new(name.to_string(), (36, y), style.clone()))?;
+        }
+
+        Ok(())
+    }
+}
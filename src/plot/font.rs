@@ -0,0 +1,597 @@
+//! The font embedded into every chart.
+//!
+//! The text of SVGs is converted to outlines of the embedded font's glyphs,
+//! and bitmaps rasterize the same outlines laid out the same way, so charts
+//! look the same on any machine and in any format, with or without the font
+//! installed.
+
+use std::{
+    collections::{btree_map, BTreeMap},
+    fmt::Write,
+    sync::Once,
+};
+
+use ab_glyph::{point, Font, FontRef, GlyphId, Outline, OutlineCurve, PxScale};
+use plotters::style::FontStyle;
+use plotters_backend::{
+    text_anchor::{HPos, VPos},
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind,
+};
+
+/// The family name the embedded font is registered as.
+pub(crate) const FONT: &str = "DejaVu Sans Mono";
+
+const FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+
+/// Register the embedded font to plotters, also as the generic families
+/// plotters falls back to for unstyled text.
+pub(crate) fn register() {
+    static REGISTER: Once = Once::new();
+
+    REGISTER.call_once(|| {
+        for family in [FONT, "sans-serif", "serif", "monospace"] {
+            plotters::style::register_font(family, FontStyle::Normal, FONT_DATA)
+                .unwrap_or_else(|_| unreachable!("invalid embedded font"));
+        }
+    });
+}
+
+/// Replace every `<text>` element of an SVG rendered by plotters with the
+/// outlines of its glyphs in the embedded font.
+///
+/// Each glyph is defined once, in font units, and referenced by every text
+/// using it.
+pub(crate) fn outline_text(svg: &str) -> String {
+    let font = FontRef::try_from_slice(FONT_DATA).expect("invalid embedded font");
+
+    let mut glyphs = BTreeMap::new();
+    let mut body = String::with_capacity(svg.len());
+    let mut rest = svg;
+
+    while let Some(start) = rest.find("<text ") {
+        body.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find("</text>") else {
+            break;
+        };
+
+        let element = &rest[..end];
+        rest = &rest[end + "</text>".len()..];
+
+        match TextElement::parse(element) {
+            Some(text) => text.write_glyphs(&font, &mut glyphs, &mut body),
+            None => {
+                body.push_str(element);
+                body.push_str("</text>");
+            }
+        }
+    }
+
+    body.push_str(rest);
+
+    let mut defs = String::from("<defs>");
+
+    for (id, d) in &glyphs {
+        let _ = write!(defs, "<path id=\"g{id}\" d=\"{d}\"/>");
+    }
+
+    defs.push_str("</defs>\n");
+
+    // Insert the glyph definitions right after the opening `<svg>` tag.
+    match body
+        .find("<svg")
+        .and_then(|start| Some(start + body[start..].find('>')?))
+    {
+        Some(end) => {
+            body.insert_str(end + 1, &format!("\n{defs}"));
+            body.insert_str(end, " xmlns:xlink=\"http://www.w3.org/1999/xlink\"");
+        }
+        None => body.insert_str(0, &defs),
+    }
+
+    body
+}
+
+/// A backend drawing text from the outlines of the embedded font, and
+/// everything else with the backend it wraps.
+///
+/// plotters places every glyph by the top of its own pixel bounds, so glyphs
+/// not reaching the cap height, like `.` or `,`, float above the baseline.
+/// Glyphs are laid out on a shared baseline here instead, the same way
+/// [`outline_text`] lays them out in SVGs.
+pub(crate) struct TextBackend<B> {
+    inner: B,
+    font: FontRef<'static>,
+}
+
+impl<B> TextBackend<B> {
+    pub(crate) fn new(inner: B) -> Self {
+        Self {
+            inner,
+            font: FontRef::try_from_slice(FONT_DATA).expect("invalid embedded font"),
+        }
+    }
+}
+
+impl<B: DrawingBackend> DrawingBackend for TextBackend<B> {
+    type ErrorType = B::ErrorType;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.inner.get_size()
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.ensure_prepared()
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.present()
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_pixel(point, color)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_line(from, to, style)
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_rect(upper_left, bottom_right, style, fill)
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_path(path, style)
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_circle(center, radius, style, fill)
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.fill_polygon(vert, style)
+    }
+
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        size: (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.blit_bitmap(pos, size, src)
+    }
+
+    fn draw_text<S: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &S,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let color = style.color();
+
+        if color.alpha == 0.0 {
+            return Ok(());
+        }
+
+        // The anchor as plotters' SVG backend writes it.
+        let anchor = match style.anchor().h_pos {
+            HPos::Left => "start",
+            HPos::Center => "middle",
+            HPos::Right => "end",
+        };
+        let dy = match style.anchor().v_pos {
+            VPos::Top => "0.76em",
+            VPos::Center => "0.5ex",
+            VPos::Bottom => "-0.5ex",
+        };
+
+        let size = style.size() as f32 / 1.24;
+        let line = Line::layout(&self.font, text, size, anchor, dy);
+
+        // ab_glyph scales glyphs by their height rather than by the em.
+        let upem = self.font.units_per_em().unwrap_or(2048.0);
+        let scale = PxScale::from(size * self.font.height_unscaled() / upem);
+
+        let transform = style.transform();
+        let (width, height) = self.get_size();
+        let mut pixels = Vec::new();
+
+        for (id, origin) in line.glyphs {
+            let glyph = id
+                .with_scale_and_position(scale, point(line.x + origin * line.scale, line.baseline));
+
+            let Some(outlined) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+
+            let bounds = outlined.px_bounds();
+
+            outlined.draw(|x, y, coverage| {
+                let (dx, dy) = transform.transform(
+                    bounds.min.x as i32 + x as i32,
+                    bounds.min.y as i32 + y as i32,
+                );
+                pixels.push(((pos.0 + dx, pos.1 + dy), coverage));
+            });
+        }
+
+        for ((x, y), coverage) in pixels {
+            if coverage <= 0.0 || x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                continue;
+            }
+
+            let color = BackendColor {
+                alpha: color.alpha * f64::from(coverage.min(1.0)),
+                rgb: color.rgb,
+            };
+
+            self.inner.draw_pixel((x, y), color)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A `<text>` element as emitted by plotters' SVG backend.
+struct TextElement<'a> {
+    x: f32,
+    y: f32,
+    dy: &'a str,
+    anchor: &'a str,
+    size: f32,
+    fill: &'a str,
+    opacity: Option<&'a str>,
+    transform: Option<&'a str>,
+    text: String,
+}
+
+impl<'a> TextElement<'a> {
+    fn parse(element: &'a str) -> Option<Self> {
+        let (tag, text) = element.split_once('>')?;
+
+        let attr = |name: &str| -> Option<&'a str> {
+            let pattern = format!(" {name}=\"");
+            let start = tag.find(&pattern)? + pattern.len();
+            let len = tag[start..].find('"')?;
+            Some(&tag[start..start + len])
+        };
+
+        Some(Self {
+            x: attr("x")?.parse().ok()?,
+            y: attr("y")?.parse().ok()?,
+            dy: attr("dy").unwrap_or("0"),
+            anchor: attr("text-anchor").unwrap_or("start"),
+            size: attr("font-size")?.parse().ok()?,
+            fill: attr("fill").unwrap_or("#000000"),
+            opacity: attr("opacity"),
+            transform: attr("transform"),
+            text: unescape(text.trim_matches('\n')),
+        })
+    }
+
+    /// Write the text as a group of glyph references, adding the outlines of
+    /// glyphs not seen yet to `glyphs`.
+    fn write_glyphs(
+        &self,
+        font: &FontRef<'_>,
+        glyphs: &mut BTreeMap<u16, String>,
+        out: &mut String,
+    ) {
+        let line = Line::layout(font, &self.text, self.size, self.anchor, self.dy);
+        let (x0, baseline, scale) = (self.x + line.x, self.y + line.baseline, line.scale);
+
+        let _ = write!(out, "<g fill=\"{}\"", self.fill);
+
+        if let Some(opacity) = self.opacity {
+            let _ = write!(out, " opacity=\"{opacity}\"");
+        }
+
+        // Glyphs are defined in font units, with the y-axis pointing up.
+        let _ = write!(
+            out,
+            " transform=\"{} translate({x0:.2} {baseline:.2}) scale({scale:.5} -{scale:.5})\">",
+            self.transform.unwrap_or_default(),
+        );
+
+        for (id, origin) in line.glyphs {
+            if let btree_map::Entry::Vacant(entry) = glyphs.entry(id.0) {
+                match font.outline(id) {
+                    Some(outline) => entry.insert(outline_path(&outline)),
+                    // Whitespace has no outline.
+                    None => continue,
+                };
+            }
+
+            let _ = write!(out, "<use xlink:href=\"#g{}\" x=\"{origin}\"/>", id.0);
+        }
+
+        out.push_str("</g>");
+    }
+}
+
+/// A line of text laid out in the embedded font, relative to the point it is
+/// anchored at.
+struct Line {
+    /// Every glyph with its origin on the line, in font units.
+    glyphs: Vec<(GlyphId, f32)>,
+    /// Where the line starts, in pixels.
+    x: f32,
+    /// Where the baseline is, in pixels.
+    baseline: f32,
+    /// Pixels per font unit.
+    scale: f32,
+}
+
+impl Line {
+    /// Lay out `text` of `size` pixels per em, with the `text-anchor` and `dy`
+    /// plotters' SVG backend positions the text with.
+    fn layout(font: &FontRef<'_>, text: &str, size: f32, anchor: &str, dy: &str) -> Self {
+        let upem = font.units_per_em().unwrap_or(2048.0);
+        let scale = size / upem;
+
+        let mut glyphs = Vec::with_capacity(text.len());
+        let mut pen = 0f32;
+        let mut last = None;
+
+        for id in text.chars().map(|c| font.glyph_id(c)) {
+            if let Some(last) = last {
+                pen += font.kern_unscaled(last, id);
+            }
+
+            glyphs.push((id, pen));
+            pen += font.h_advance_unscaled(id);
+            last = Some(id);
+        }
+
+        let x = match anchor {
+            "middle" => -pen * scale / 2.0,
+            "end" => -pen * scale,
+            _ => 0.0,
+        };
+
+        // The baseline shift plotters expresses in font relative units. The
+        // bounds of outlines are y-flipped, but `x` sits on the baseline.
+        let ex = font
+            .outline(font.glyph_id('x'))
+            .map_or(upem / 2.0, |outline| outline.bounds.height().abs())
+            * scale;
+        let baseline = match dy {
+            "0.76em" => 0.76 * size,
+            "0.5ex" => 0.5 * ex,
+            "-0.5ex" => -0.5 * ex,
+            _ => 0.0,
+        };
+
+        Self {
+            glyphs,
+            x,
+            baseline,
+            scale,
+        }
+    }
+}
+
+/// The SVG path data of a glyph outline, in font units.
+fn outline_path(outline: &Outline) -> String {
+    let mut d = String::new();
+    let mut last = None;
+
+    for curve in &outline.curves {
+        let (start, end) = match curve {
+            OutlineCurve::Line(p0, p1) => (*p0, *p1),
+            OutlineCurve::Quad(p0, _, p2) => (*p0, *p2),
+            OutlineCurve::Cubic(p0, _, _, p3) => (*p0, *p3),
+        };
+
+        // A new contour starts wherever the previous curve did not end.
+        if last != Some(start) {
+            if last.is_some() {
+                d.push('Z');
+            }
+
+            let _ = write!(d, "M{} {}", start.x, start.y);
+        }
+
+        let _ = match curve {
+            OutlineCurve::Line(_, p1) => write!(d, "L{} {}", p1.x, p1.y),
+            OutlineCurve::Quad(_, p1, p2) => write!(d, "Q{} {} {} {}", p1.x, p1.y, p2.x, p2.y),
+            OutlineCurve::Cubic(_, p1, p2, p3) => {
+                write!(d, "C{} {} {} {} {} {}", p1.x, p1.y, p2.x, p2.y, p3.x, p3.y)
+            }
+        };
+
+        last = Some(end);
+    }
+
+    if last.is_some() {
+        d.push('Z');
+    }
+
+    d
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use plotters::{prelude::*, style::text_anchor::Pos};
+
+    use super::*;
+
+    fn font() -> FontRef<'static> {
+        FontRef::try_from_slice(FONT_DATA).unwrap()
+    }
+
+    #[test]
+    fn parse_text_element() {
+        let element = r##"<text x="10" y="20.5" dy="0.76em" text-anchor="middle" font-family="DejaVu Sans Mono" font-size="9.67" opacity="0.5" fill="#FF0000" transform="rotate(90, 10, 20.5)">
+a &lt;b&gt; &amp; c
+"##;
+
+        let text = TextElement::parse(element).unwrap();
+
+        assert_eq!((text.x, text.y, text.size), (10.0, 20.5, 9.67));
+        assert_eq!(
+            (text.dy, text.anchor, text.fill),
+            ("0.76em", "middle", "#FF0000")
+        );
+        assert_eq!(text.opacity, Some("0.5"));
+        assert_eq!(text.transform, Some("rotate(90, 10, 20.5)"));
+        assert_eq!(text.text, "a <b> & c");
+    }
+
+    #[test]
+    fn parse_defaults_and_rejects() {
+        let text = TextElement::parse(r#"<text x="1" y="2" font-size="12">x"#).unwrap();
+
+        assert_eq!((text.dy, text.anchor, text.fill), ("0", "start", "#000000"));
+        assert_eq!((text.opacity, text.transform), (None, None));
+
+        assert!(TextElement::parse(r#"<text y="2" font-size="12">x"#).is_none());
+        assert!(TextElement::parse(r#"<text x="a" y="2" font-size="12">x"#).is_none());
+        assert!(TextElement::parse(r#"<text x="1" y="2" font-size="12""#).is_none());
+    }
+
+    #[test]
+    fn unescape_entities() {
+        assert_eq!(unescape("&amp;lt; &quot;&apos;"), "&lt; \"'");
+    }
+
+    #[test]
+    fn layout_anchors() {
+        let font = font();
+        let start = Line::layout(&font, "ab", 10.0, "start", "0");
+        let middle = Line::layout(&font, "ab", 10.0, "middle", "0");
+        let end = Line::layout(&font, "ab", 10.0, "end", "0");
+
+        // A monospace font advances every glyph by the same width.
+        let advance = font.h_advance_unscaled(font.glyph_id('a'));
+        assert_eq!(
+            start.glyphs.iter().map(|g| g.1).collect::<Vec<_>>(),
+            [0.0, advance]
+        );
+
+        let width = 2.0 * advance * start.scale;
+        assert_eq!(start.x, 0.0);
+        assert!((middle.x + width / 2.0).abs() < 1e-3);
+        assert!((end.x + width).abs() < 1e-3);
+    }
+
+    #[test]
+    fn layout_baselines() {
+        let font = font();
+        let baseline = |dy| Line::layout(&font, "x", 10.0, "start", dy).baseline;
+
+        assert_eq!(baseline("0"), 0.0);
+        assert!((baseline("0.76em") - 7.6).abs() < 1e-3);
+        // Half the x-height of DejaVu Sans Mono, 1120 of 2048 units per em.
+        assert!((baseline("0.5ex") - 0.5 * 1120.0 / 2048.0 * 10.0).abs() < 1e-3);
+        assert_eq!(baseline("-0.5ex"), -baseline("0.5ex"));
+    }
+
+    #[test]
+    fn outline_text_shares_glyphs() {
+        let svg = r#"<svg width="10" height="10">
+<rect x="0" y="0"/>
+<text x="1" y="2" font-size="12">a.a
+</text>
+<text x="3" y="4" font-size="12">.
+</text>
+<text font-size="12">kept
+</text>
+</svg>"#;
+
+        let out = outline_text(svg);
+        let id = |c| font().glyph_id(c).0;
+
+        assert!(!out.contains("<text x="));
+        assert!(out.contains(r#"<text font-size="12">kept"#));
+        assert!(out.contains(r#"<rect x="0" y="0"/>"#));
+        assert!(out.starts_with(
+            r#"<svg width="10" height="10" xmlns:xlink="http://www.w3.org/1999/xlink">"#
+        ));
+
+        // Each glyph is defined once and referenced by every use.
+        assert_eq!(out.matches("<path id=").count(), 2);
+        assert_eq!(
+            out.matches(&format!("<path id=\"g{}\"", id('a'))).count(),
+            1
+        );
+        assert_eq!(out.matches(&format!("href=\"#g{}\"", id('a'))).count(), 2);
+        assert_eq!(out.matches(&format!("href=\"#g{}\"", id('.'))).count(), 2);
+    }
+
+    #[test]
+    fn outline_path_closes_contours() {
+        let font = font();
+        // `o` has an outer and an inner contour.
+        let d = outline_path(&font.outline(font.glyph_id('o')).unwrap());
+
+        assert_eq!(d.matches('M').count(), 2);
+        assert_eq!(d.matches('Z').count(), 2);
+        assert!(d.starts_with('M') && d.ends_with('Z'));
+    }
+
+    /// The lowest row a text covers, drawn at the top of a 100x40 bitmap.
+    fn bottom(text: &str) -> usize {
+        let mut buf = vec![255; 100 * 40 * 3];
+
+        {
+            let root = TextBackend::new(BitMapBackend::with_buffer(&mut buf, (100, 40)))
+                .into_drawing_area();
+            let style = TextStyle::from((FONT, 24).into_font())
+                .pos(Pos::new(HPos::Left, VPos::Top))
+                .color(&BLACK);
+
+            root.draw_text(text, &style, (0, 0)).unwrap();
+            root.present().unwrap();
+        }
+
+        buf.chunks(100 * 3)
+            .rposition(|row| row.iter().any(|&c| c < 128))
+            .unwrap()
+    }
+
+    #[test]
+    fn bitmap_glyphs_share_the_baseline() {
+        assert_eq!(bottom("."), bottom("0"));
+        assert_eq!(bottom("0.2"), bottom("0"));
+        assert!(bottom(",") > bottom("0"));
+    }
+}
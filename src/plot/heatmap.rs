@@ -0,0 +1,334 @@
+//! Heatmap overview of map families across workloads or thread counts.
+//!
+//! Every cell prints the throughput of a map family and is colored by how it
+//! compares to the best cell of its row or column, from red to green.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs,
+};
+
+use plotters::{
+    coord::Shift,
+    prelude::*,
+    style::text_anchor::{HPos, Pos, VPos},
+};
+
+use super::{caption_width, family, font, record_at, render, split_source, Groups, Output, FONT};
+use crate::args::{AtThreads, HeatmapColumns, HeatmapRelative, ImageFormat};
+
+const CELL_HEIGHT: u32 = 26;
+const MIN_CELL_WIDTH: u32 = 80;
+
+struct Heatmap {
+    caption: String,
+    columns: Vec<String>,
+    rows: Vec<Row>,
+    relative: HeatmapRelative,
+}
+
+struct Row {
+    label: String,
+    cells: Vec<Option<f64>>,
+}
+
+/// Plot the throughput of every map family of every source as a heatmap.
+pub(crate) fn plot_heatmap(
+    sources: &[(String, Groups)],
+    output: &Output,
+    at: AtThreads,
+    columns: HeatmapColumns,
+    relative: HeatmapRelative,
+) -> Result<(), Box<dyn Error>> {
+    let heatmap = match columns {
+        HeatmapColumns::Workload => by_workload(sources, &output.name, at),
+        HeatmapColumns::Threads => by_threads(sources, &output.name),
+    };
+
+    let heatmap = Heatmap {
+        relative,
+        ..heatmap.sorted()
+    };
+
+    if heatmap.rows.is_empty() {
+        return Err(format!("no results for the heatmap of {}", output.name).into());
+    }
+
+    // Grow the chart rather than squeezing the cells or clipping the caption.
+    let label_width = heatmap.label_width();
+    let cell_width = heatmap
+        .columns
+        .iter()
+        .map(|column| column.chars().count() as u32 * 8 + 12)
+        .fold(MIN_CELL_WIDTH, u32::max);
+
+    let output = Output {
+        width: output
+            .width
+            .max(label_width + heatmap.columns.len() as u32 * cell_width + 40)
+            .max(caption_width(&heatmap.caption)),
+        height: output
+            .height
+            .max(heatmap.rows.len() as u32 * CELL_HEIGHT + 130),
+        ..output.clone()
+    };
+
+    render!(&output, "heatmap", |root| draw_heatmap(&root, &heatmap)?);
+
+    Ok(())
+}
+
+/// One column per source, at `at` threads.
+fn by_workload(sources: &[(String, Groups)], name: &str, at: AtThreads) -> Heatmap {
+    let hashers = sources
+        .iter()
+        .map(|(source, _)| split_source(source).1)
+        .collect::<BTreeSet<_>>();
+
+    // The hasher is only worth a column header when the inputs differ in it.
+    let columns = sources
+        .iter()
+        .map(|(source, _)| match hashers.len() {
+            1 => split_source(source).0.to_owned(),
+            _ => source.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut rows = BTreeMap::<_, Vec<_>>::new();
+
+    for (column, (_, groups)) in sources.iter().enumerate() {
+        for (name, records) in groups.iter() {
+            let cells = rows
+                .entry(family(name))
+                .or_insert_with(|| vec![None; sources.len()]);
+            cells[column] = record_at(records, at).map(|record| record.throughput);
+        }
+    }
+
+    Heatmap {
+        caption: format!(
+            "{}: Throughput (Mop/s) at {} @ {}",
+            name,
+            at,
+            env!("CARGO_PKG_VERSION")
+        ),
+        columns,
+        rows: rows
+            .into_iter()
+            .map(|(label, cells)| Row { label, cells })
+            .collect(),
+        relative: HeatmapRelative::Column,
+    }
+}
+
+/// One column per thread count, with a row per map family of every source.
+fn by_threads(sources: &[(String, Groups)], name: &str) -> Heatmap {
+    let threads = sources
+        .iter()
+        .flat_map(|(_, groups)| groups.values().flatten())
+        .map(|record| record.threads)
+        .collect::<BTreeSet<_>>();
+
+    let rows = sources
+        .iter()
+        .flat_map(|(source, groups)| {
+            let threads = &threads;
+
+            groups.iter().map(move |(name, records)| Row {
+                label: match sources.len() {
+                    1 => family(name),
+                    _ => format!("{} ({})", family(name), source),
+                },
+                cells: threads
+                    .iter()
+                    .map(|n| {
+                        let record = record_at(records, AtThreads::Count(*n))?;
+                        Some(record.throughput)
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+
+    Heatmap {
+        caption: format!(
+            "{}: Throughput (Mop/s) @ {}",
+            name,
+            env!("CARGO_PKG_VERSION")
+        ),
+        columns: threads.iter().map(|n| format!("{} threads", n)).collect(),
+        rows,
+        relative: HeatmapRelative::Column,
+    }
+}
+
+impl Heatmap {
+    /// The best value of every column.
+    fn column_best(&self) -> Vec<f64> {
+        (0..self.columns.len())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.cells[column])
+                    .fold(0f64, f64::max)
+            })
+            .collect()
+    }
+
+    /// Sort the rows by their mean throughput relative to the best of each
+    /// column, best first.
+    fn sorted(mut self) -> Self {
+        let best = self.column_best();
+        let score = |row: &Row| {
+            let relative = row
+                .cells
+                .iter()
+                .zip(&best)
+                .filter_map(|(cell, best)| Some((*cell)? / best))
+                .collect::<Vec<_>>();
+
+            relative.iter().sum::<f64>() / relative.len().max(1) as f64
+        };
+
+        self.rows
+            .sort_by(|a, b| score(b).total_cmp(&score(a)).then(a.label.cmp(&b.label)));
+        self
+    }
+
+    /// The value of every cell relative to the best of its row or column.
+    fn relative_cells(&self) -> Vec<Vec<Option<f64>>> {
+        let column_best = self.column_best();
+
+        self.rows
+            .iter()
+            .map(|row| {
+                let row_best = row
+                    .cells
+                    .iter()
+                    .flatten()
+                    .fold(0f64, |res, cur| res.max(*cur));
+
+                row.cells
+                    .iter()
+                    .zip(&column_best)
+                    .map(|(cell, column_best)| {
+                        let best = match self.relative {
+                            HeatmapRelative::Row => row_best,
+                            HeatmapRelative::Column => *column_best,
+                        };
+
+                        Some((*cell)? / best)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The width of the row labels, in a monospace font.
+    fn label_width(&self) -> u32 {
+        self.rows
+            .iter()
+            .map(|row| row.label.chars().count())
+            .max()
+            .unwrap_or_default() as u32
+            * 8
+            + 16
+    }
+}
+
+/// From red for the worst to green for the best.
+fn cell_color(relative: f64) -> HSLColor {
+    HSLColor(relative.clamp(0.0, 1.0) / 3.0, 0.65, 0.6)
+}
+
+fn draw_heatmap<DB>(root: &DrawingArea<DB, Shift>, heatmap: &Heatmap) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let columns = heatmap.columns.len();
+    let rows = heatmap.rows.len();
+    let label_width = heatmap.label_width();
+
+    let mut chart = ChartBuilder::on(root)
+        .margin(10)
+        .caption(&heatmap.caption, (FONT, 20))
+        .set_label_area_size(LabelAreaPosition::Left, label_width)
+        .set_label_area_size(LabelAreaPosition::Top, 24)
+        .set_label_area_size(LabelAreaPosition::Bottom, 24)
+        .build_cartesian_2d(0.0..columns as f64, 0.0..rows as f64)?;
+
+    let text = TextStyle::from((FONT, 13).into_font());
+    let center = text.pos(Pos::new(HPos::Center, VPos::Center));
+
+    for (column, label) in heatmap.columns.iter().enumerate() {
+        chart.draw_series(std::iter::once(
+            EmptyElement::at((column as f64 + 0.5, rows as f64))
+                + Text::new(
+                    label.clone(),
+                    (0, -6),
+                    text.pos(Pos::new(HPos::Center, VPos::Bottom)),
+                ),
+        ))?;
+    }
+
+    // Rows are laid out top to bottom, the first one at the top of the chart.
+    for ((i, row), relative) in heatmap
+        .rows
+        .iter()
+        .enumerate()
+        .zip(heatmap.relative_cells())
+    {
+        let y = (rows - i - 1) as f64;
+
+        chart.draw_series(std::iter::once(
+            EmptyElement::at((0.0, y + 0.5))
+                + Text::new(
+                    row.label.clone(),
+                    (-6, 0),
+                    text.pos(Pos::new(HPos::Right, VPos::Center)),
+                ),
+        ))?;
+
+        for (column, (cell, relative)) in row.cells.iter().zip(relative).enumerate() {
+            let x = column as f64;
+
+            let (fill, value) = match (cell, relative) {
+                (Some(cell), Some(relative)) => (
+                    cell_color(relative).filled(),
+                    format!("{:.1}", cell / 1_000_000.),
+                ),
+                _ => (WHITE.mix(0.0).filled(), "-".to_owned()),
+            };
+
+            chart.draw_series(std::iter::once(Rectangle::new(
+                [(x, y), (x + 1.0, y + 1.0)],
+                fill,
+            )))?;
+            chart.draw_series(std::iter::once(Rectangle::new(
+                [(x, y), (x + 1.0, y + 1.0)],
+                WHITE.stroke_width(2),
+            )))?;
+            chart.draw_series(std::iter::once(Text::new(
+                value,
+                (x + 0.5, y + 0.5),
+                center.clone(),
+            )))?;
+        }
+    }
+
+    let note = match heatmap.relative {
+        HeatmapRelative::Row => "Colored relative to the best of each row",
+        HeatmapRelative::Column => "Colored relative to the best of each column",
+    };
+
+    chart.draw_series(std::iter::once(
+        EmptyElement::at((0.0, 0.0))
+            + Text::new(note, (0, 8), text.pos(Pos::new(HPos::Left, VPos::Top))),
+    ))?;
+
+    Ok(())
+}
@@ -0,0 +1,453 @@
+//! Colors, markers and dash patterns of the series.
+//!
+//! Every series is styled from its map family alone, so adding a map or
+//! bumping a crate version leaves the other series untouched. A theme file can
+//! override the style of any family, either with a color of its own or with one
+//! of [`SLOTS`] colors of the [`palette`], and [`palette`] generates evenly
+//! spaced colors to start one from.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs,
+    path::Path,
+};
+
+use plotters::{
+    coord::Shift,
+    element::{Drawable, PointCollection},
+    prelude::*,
+};
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+use serde::Deserialize;
+
+use super::{family, Groups};
+use crate::record::Record;
+
+/// Style overrides read from a TOML file, e.g.
+///
+/// ```toml
+/// [series."dashmap"]
+/// color = "#1f77b4"
+///
+/// [series."flurry"]
+/// slot = 3
+/// marker = "circle"
+/// dash = "solid"
+/// ```
+///
+/// Series are matched by their full name first, then by their map family. A
+/// `color` wins over a `slot`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Theme {
+    #[serde(default)]
+    series: BTreeMap<String, SeriesTheme>,
+
+    /// The dash pattern of the series of every source, when several are
+    /// plotted together.
+    #[serde(skip)]
+    dashes: BTreeMap<String, Dash>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SeriesTheme {
+    color: Option<Hex>,
+    slot: Option<Slot>,
+    marker: Option<Marker>,
+    dash: Option<Dash>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+struct Hex(RGBColor);
+
+impl TryFrom<String> for Hex {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let err = || format!("invalid color '{}', expected e.g. '#1f77b4'", s);
+
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6)
+            .ok_or_else(err)?;
+        let rgb = u32::from_str_radix(hex, 16).map_err(|_| err())?;
+
+        Ok(Self(RGBColor(
+            (rgb >> 16) as u8,
+            (rgb >> 8) as u8,
+            rgb as u8,
+        )))
+    }
+}
+
+/// The number of colors of the palette a theme can pick by slot.
+pub(crate) const SLOTS: usize = 16;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "usize")]
+struct Slot(usize);
+
+impl TryFrom<usize> for Slot {
+    type Error = String;
+
+    fn try_from(slot: usize) -> Result<Self, Self::Error> {
+        if slot < SLOTS {
+            Ok(Self(slot))
+        } else {
+            Err(format!(
+                "invalid slot {}, expected 0 to {}",
+                slot,
+                SLOTS - 1
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Marker {
+    None,
+    Circle,
+    Square,
+    Triangle,
+    Cross,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Dash {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+const DASHES: [Dash; 3] = [Dash::Solid, Dash::Dashed, Dash::Dotted];
+
+impl Dash {
+    /// The dash length and the gap between dashes, in pixels.
+    fn pattern(self) -> (u32, u32) {
+        match self {
+            Self::Solid => (20, 0),
+            Self::Dashed => (8, 4),
+            Self::Dotted => (2, 3),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SeriesStyle {
+    pub color: RGBColor,
+    pub marker: Marker,
+    pub dash: Dash,
+}
+
+impl Theme {
+    pub(crate) fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let theme = fs::read_to_string(path)
+            .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+
+        toml::from_str(&theme).map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    /// Tell the given sources apart by their dash pattern, in order, rather
+    /// than by a hash of their name which may collide.
+    pub(crate) fn assign_dashes<'a>(&mut self, sources: impl IntoIterator<Item = &'a str>) {
+        for (i, source) in sources.into_iter().enumerate() {
+            self.dashes
+                .insert(source.to_owned(), DASHES[i % DASHES.len()]);
+        }
+    }
+
+    /// The style of the series of `records`.
+    pub(crate) fn series_style(&self, records: &[Record]) -> SeriesStyle {
+        let record = &records[0];
+        self.style(&record.name, record.source.as_deref())
+    }
+
+    /// The style of the series `name`, read from `source` if several inputs
+    /// are overlaid.
+    fn style(&self, name: &str, source: Option<&str>) -> SeriesStyle {
+        let family = family(name);
+        let hash = fnv1a(&family);
+
+        let overrides = self
+            .series
+            .get(name)
+            .or_else(|| self.series.get(&family))
+            .cloned()
+            .unwrap_or_default();
+
+        // Spread the markers over other bits than the hue, so that families
+        // of close hues are still told apart.
+        const MARKERS: [Marker; 4] = [
+            Marker::Circle,
+            Marker::Square,
+            Marker::Triangle,
+            Marker::Cross,
+        ];
+
+        SeriesStyle {
+            color: match (overrides.color, overrides.slot) {
+                (Some(hex), _) => hex.0,
+                (None, Some(slot)) => palette(SLOTS)[slot.0],
+                (None, None) => hashed_color(hash),
+            },
+            marker: overrides
+                .marker
+                .unwrap_or(MARKERS[(hash >> 32) as usize % MARKERS.len()]),
+            dash: overrides.dash.unwrap_or_else(|| match source {
+                Some(source) => self
+                    .dashes
+                    .get(source)
+                    .copied()
+                    .unwrap_or(DASHES[fnv1a(source) as usize % DASHES.len()]),
+                None => Dash::Solid,
+            }),
+        }
+    }
+}
+
+impl SeriesStyle {
+    /// Draw the series as a line with markers, and register its legend entry.
+    pub(crate) fn draw<DB, X, Y>(
+        &self,
+        chart: &mut ChartContext<'_, DB, Cartesian2d<X, Y>>,
+        points: &[(X::ValueType, Y::ValueType)],
+        label: &str,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+        X: Ranged,
+        Y: Ranged,
+        X::ValueType: Clone + 'static,
+        Y::ValueType: Clone + 'static,
+    {
+        let line = ShapeStyle::from(self.color).stroke_width(1);
+        let points = points.iter().cloned();
+
+        let anno = match self.dash {
+            Dash::Solid => chart.draw_series(LineSeries::new(points.clone(), line))?,
+            dash => {
+                let (size, spacing) = dash.pattern();
+                chart.draw_series(DashedLineSeries::new(points.clone(), size, spacing, line))?
+            }
+        };
+
+        let style = *self;
+
+        anno.label(label)
+            .legend(move |(x, y)| Glyph::sample((x + 10, y), style));
+
+        chart.draw_series(points.map(|point| EmptyElement::at(point) + Glyph::marker(*self)))?;
+
+        Ok(())
+    }
+
+    /// Draw the legend sample of the series centered at `at`, outside of any
+    /// chart.
+    pub(crate) fn draw_legend<DB>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        at: (i32, i32),
+    ) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        area.draw(&Glyph::sample(at, *self))?;
+
+        Ok(())
+    }
+}
+
+/// The marker of a series, with a stretch of its line for legends, drawn in
+/// pixels around its anchor.
+///
+/// Plotters composes dynamic elements for `'static` backends only, hence an
+/// element of our own rather than one per marker.
+struct Glyph {
+    at: BackendCoord,
+    style: SeriesStyle,
+    line: bool,
+}
+
+impl Glyph {
+    fn marker(style: SeriesStyle) -> Self {
+        Self {
+            at: (0, 0),
+            style,
+            line: false,
+        }
+    }
+
+    fn sample(at: BackendCoord, style: SeriesStyle) -> Self {
+        Self {
+            at,
+            style,
+            line: true,
+        }
+    }
+}
+
+impl<'a> PointCollection<'a, BackendCoord> for &'a Glyph {
+    type Point = &'a BackendCoord;
+    type IntoIter = std::iter::Once<&'a BackendCoord>;
+
+    fn point_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.at)
+    }
+}
+
+impl<DB: DrawingBackend> Drawable<DB> for Glyph {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut pos: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let Some((x, y)) = pos.next() else {
+            return Ok(());
+        };
+
+        let color = self.style.color;
+
+        if self.line {
+            let (size, spacing) = self.style.dash.pattern();
+            let line = color.stroke_width(2);
+            let mut from = x - 10;
+
+            while from < x + 10 {
+                let to = (from + size as i32).min(x + 10);
+                backend.draw_line((from, y), (to, y), &line)?;
+                from = to + spacing as i32;
+            }
+        }
+
+        match self.style.marker {
+            Marker::None => {}
+            Marker::Circle => backend.draw_circle((x, y), 3, &color.filled(), true)?,
+            Marker::Square => {
+                backend.draw_rect((x - 3, y - 3), (x + 3, y + 3), &color.filled(), true)?
+            }
+            Marker::Triangle => backend.fill_polygon(
+                [(x, y - 4), (x - 4, y + 3), (x + 4, y + 3)],
+                &color.filled(),
+            )?,
+            Marker::Cross => {
+                let line = color.stroke_width(2);
+                backend.draw_line((x - 3, y - 3), (x + 3, y + 3), &line)?;
+                backend.draw_line((x - 3, y + 3), (x + 3, y - 3), &line)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A stable hash of `s`, independent of the platform and of the toolchain.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// A saturated color of a hue picked by `hash`.
+fn hashed_color(hash: u64) -> RGBColor {
+    let hue = (hash % 360) as f64 / 360.;
+    let lightness = 0.40 + ((hash >> 16) % 16) as f64 / 100.;
+
+    to_rgb(HSLColor(hue, 0.85, lightness))
+}
+
+/// `n` colors of evenly spaced hues, alternating between two lightnesses so
+/// that neighbouring hues stand out.
+pub(crate) fn palette(n: usize) -> Vec<RGBColor> {
+    (0..n)
+        .map(|i| {
+            let hue = i as f64 / n as f64;
+            let lightness = if i % 2 == 0 { 0.45 } else { 0.55 };
+
+            to_rgb(HSLColor(hue, 0.9, lightness))
+        })
+        .collect()
+}
+
+fn to_rgb(color: impl Color) -> RGBColor {
+    let (r, g, b) = color.to_rgba().rgb();
+    RGBColor(r, g, b)
+}
+
+/// `#rrggbb`
+pub(crate) fn hex(color: RGBColor) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+/// A theme assigning a color of the palette to every map family of `sources`,
+/// to be edited at will.
+pub(crate) fn palette_theme(sources: &[(String, Groups)]) -> String {
+    let families = sources
+        .iter()
+        .flat_map(|(_, groups)| groups.keys())
+        .map(|name| family(name))
+        .collect::<BTreeSet<_>>();
+
+    families
+        .iter()
+        .zip(palette(families.len()))
+        .map(|(family, color)| format!("[series.{:?}]\ncolor = \"{}\"\n\n", family, hex(color)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme(toml: &str) -> Theme {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn families_keep_their_color_when_another_is_added() {
+        let theme = Theme::default();
+        let before = theme.style("dashmap@5.5.3", None).color;
+
+        let mut added = Theme::default();
+        added.assign_dashes(["a", "b"]);
+
+        assert_eq!(added.style("dashmap@5.5.3", None).color, before);
+        assert_eq!(added.style("dashmap@6.1.0", None).color, before);
+        assert_ne!(added.style("flurry@0.5.1", None).color, before);
+    }
+
+    #[test]
+    fn theme_colors_win_over_slots_and_hashes() {
+        let theme = theme(
+            r##"
+            [series."dashmap"]
+            slot = 3
+
+            [series."flurry"]
+            color = "#010203"
+            slot = 3
+            "##,
+        );
+
+        assert_eq!(theme.style("dashmap@5.5.3", None).color, palette(SLOTS)[3]);
+        assert_eq!(theme.style("flurry@0.5.1", None).color, RGBColor(1, 2, 3));
+        assert_eq!(
+            theme.style("contrie@0.1.4", None).color,
+            hashed_color(fnv1a("contrie"))
+        );
+    }
+
+    #[test]
+    fn invalid_slots_and_colors() {
+        assert!(toml::from_str::<Theme>("[series.a]\nslot = 16").is_err());
+        assert!(toml::from_str::<Theme>("[series.a]\ncolor = \"1f77b4\"").is_err());
+        assert!(toml::from_str::<Theme>("[series.a]\ncolor = \"#1f77bz\"").is_err());
+    }
+}
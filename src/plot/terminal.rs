@@ -0,0 +1,404 @@
+//! Charts and tables printed to the terminal, for hosts reached over SSH.
+//!
+//! Curves are rasterized onto a grid of braille dots or half blocks, every
+//! character taking the color of the last series crossing it. Colors are only
+//! emitted to terminals, and never with `NO_COLOR` set.
+
+use std::{
+    env,
+    error::Error,
+    io::{self, IsTerminal, Write},
+};
+
+use plotters::style::RGBColor;
+
+use super::{Groups, Output};
+use crate::{args::TerminalChars, record::Record};
+
+/// The height of the charts, in characters.
+const ROWS: usize = 16;
+
+/// The width of the y labels, in characters.
+const LABEL_WIDTH: usize = 10;
+
+/// The value a chart plots of every record.
+type Metric = fn(&Record) -> f64;
+
+/// Print the throughput and latency charts of every source, followed by the
+/// peak throughput of every map.
+pub(crate) fn print_terminal(
+    sources: &[(String, Groups)],
+    output: &Output,
+    latency_limit_ns: u64,
+    chars: TerminalChars,
+) -> Result<(), Box<dyn Error>> {
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let width = terminal_width().clamp(40, 200);
+    let mut out = io::stdout().lock();
+
+    for (source, groups) in sources {
+        let charts: [(&str, Metric, Option<f64>); 2] = [
+            (
+                "Throughput (Mop/s)",
+                |record| record.throughput / 1_000_000.,
+                None,
+            ),
+            (
+                "Latency (ns)",
+                |record| record.latency.as_nanos() as f64,
+                Some(latency_limit_ns as f64),
+            ),
+        ];
+
+        for (title, value, limit) in charts {
+            writeln!(out, "{}: {}", source, title)?;
+
+            let chart = Chart::new(groups, output, value, limit, width, chars);
+            for line in chart.render(color) {
+                writeln!(out, "{}", line)?;
+            }
+
+            writeln!(out)?;
+        }
+
+        print_legend(&mut out, groups, output, width, chars, color)?;
+        print_peaks(&mut out, source, groups)?;
+    }
+
+    Ok(())
+}
+
+struct Chart {
+    canvas: Canvas,
+    x: (u32, u32),
+    y_max: f64,
+}
+
+impl Chart {
+    fn new(
+        groups: &Groups,
+        output: &Output,
+        value: Metric,
+        limit: Option<f64>,
+        width: usize,
+        chars: TerminalChars,
+    ) -> Self {
+        let records = || groups.values().flatten();
+
+        let x_min = records().map(|record| record.threads).min().unwrap_or(1);
+        let x_max = records()
+            .map(|record| record.threads)
+            .max()
+            .unwrap_or(1)
+            .max(x_min + 1);
+        let y_max = records()
+            .map(value)
+            .fold(0f64, f64::max)
+            .min(limit.unwrap_or(f64::INFINITY))
+            .max(f64::MIN_POSITIVE);
+
+        let mut canvas = Canvas::new(width - LABEL_WIDTH - 1, ROWS, chars);
+        let (dots_x, dots_y) = canvas.dots();
+
+        // Dots of the value at the top of the chart, clipped to it.
+        let to_dots = |record: &Record| {
+            let x = (record.threads - x_min) as f64 / (x_max - x_min) as f64;
+            let y = value(record).min(y_max) / y_max;
+
+            (
+                (x * (dots_x - 1) as f64).round() as i64,
+                ((1. - y) * (dots_y - 1) as f64).round() as i64,
+            )
+        };
+
+        for records in groups.values() {
+            let color = output.theme.series_style(records).color;
+            let points = records.iter().map(to_dots).collect::<Vec<_>>();
+
+            for pair in points.windows(2) {
+                canvas.line(pair[0], pair[1], color);
+            }
+
+            if let [point] = points[..] {
+                canvas.set(point, color);
+            }
+        }
+
+        Self {
+            canvas,
+            x: (x_min, x_max),
+            y_max,
+        }
+    }
+
+    fn render(&self, color: bool) -> Vec<String> {
+        let rows = self.canvas.render(color);
+        let cols = self.canvas.cols;
+
+        let mut lines = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let label = match i {
+                    0 => format!("{:.1}", self.y_max),
+                    i if i == ROWS / 2 => format!("{:.1}", self.y_max / 2.),
+                    i if i == ROWS - 1 => "0".to_owned(),
+                    _ => String::new(),
+                };
+                let tick = if label.is_empty() { '│' } else { '┤' };
+
+                format!("{:>w$}{}{}", label, tick, row, w = LABEL_WIDTH)
+            })
+            .collect::<Vec<_>>();
+
+        let (x_min, x_max) = self.x;
+        let x_max = x_max.to_string();
+
+        lines.push(format!("{:>w$}└{}", "", "─".repeat(cols), w = LABEL_WIDTH));
+        lines.push(format!(
+            "{:>w$} {:<a$}{}",
+            "",
+            x_min,
+            x_max,
+            w = LABEL_WIDTH,
+            a = cols.saturating_sub(x_max.len()),
+        ));
+        lines.push(format!(
+            "{:>w$} {:^c$}",
+            "",
+            "Threads",
+            w = LABEL_WIDTH,
+            c = cols
+        ));
+
+        lines
+    }
+}
+
+/// A grid of characters made of dots, braille ones holding 2x4 dots and half
+/// blocks 1x2.
+struct Canvas {
+    cols: usize,
+    rows: usize,
+    chars: TerminalChars,
+    cells: Vec<(u8, Option<RGBColor>)>,
+}
+
+impl Canvas {
+    fn new(cols: usize, rows: usize, chars: TerminalChars) -> Self {
+        Self {
+            cols,
+            rows,
+            chars,
+            cells: vec![(0, None); cols * rows],
+        }
+    }
+
+    /// The dots of a character, horizontally and vertically.
+    fn cell_dots(&self) -> (usize, usize) {
+        match self.chars {
+            TerminalChars::Braille => (2, 4),
+            TerminalChars::Block => (1, 2),
+        }
+    }
+
+    /// The dots of the canvas, horizontally and vertically.
+    fn dots(&self) -> (usize, usize) {
+        let (x, y) = self.cell_dots();
+        (self.cols * x, self.rows * y)
+    }
+
+    fn set(&mut self, (x, y): (i64, i64), color: RGBColor) {
+        let (dots_x, dots_y) = self.dots();
+
+        if x < 0 || y < 0 || x as usize >= dots_x || y as usize >= dots_y {
+            return;
+        }
+
+        let (cell_x, cell_y) = self.cell_dots();
+        let (x, y) = (x as usize, y as usize);
+        let (dx, dy) = (x % cell_x, y % cell_y);
+
+        let bit = match self.chars {
+            // The braille dots are numbered down the left column first, with
+            // the bottom row added last.
+            TerminalChars::Braille => match (dx, dy) {
+                (0, 3) => 0x40,
+                (1, 3) => 0x80,
+                (0, dy) => 1 << dy,
+                (_, dy) => 1 << (dy + 3),
+            },
+            TerminalChars::Block => 1 << dy,
+        };
+
+        let cell = &mut self.cells[y / cell_y * self.cols + x / cell_x];
+        cell.0 |= bit;
+        cell.1 = Some(color);
+    }
+
+    /// Bresenham's line between two dots.
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: RGBColor) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+
+        loop {
+            self.set((x, y), color);
+
+            if (x, y) == (x1, y1) {
+                break;
+            }
+
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    fn render(&self, color: bool) -> Vec<String> {
+        self.cells
+            .chunks(self.cols)
+            .map(|row| {
+                row.iter()
+                    .map(|&(bits, fg)| {
+                        let c = match self.chars {
+                            TerminalChars::Braille => char::from_u32(0x2800 + bits as u32).unwrap(),
+                            TerminalChars::Block => [' ', '▀', '▄', '█'][bits as usize],
+                        };
+
+                        match fg {
+                            Some(fg) if color && bits != 0 => paint(c, fg),
+                            _ if bits == 0 => " ".to_owned(),
+                            _ => c.to_string(),
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn paint(s: impl std::fmt::Display, color: RGBColor) -> String {
+    format!("\x1b[38;2;{};{};{}m{}\x1b[0m", color.0, color.1, color.2, s)
+}
+
+fn print_legend(
+    out: &mut impl Write,
+    groups: &Groups,
+    output: &Output,
+    width: usize,
+    chars: TerminalChars,
+    color: bool,
+) -> io::Result<()> {
+    let sample = match chars {
+        TerminalChars::Braille => "⣀⣀",
+        TerminalChars::Block => "▄▄",
+    };
+
+    let mut line_width = 0;
+
+    for (name, records) in groups.iter() {
+        // Without colors the curves can't be told apart, and the legend
+        // merely lists the series.
+        let marker = match color {
+            true => sample,
+            false => "•",
+        };
+        let item_width = marker.chars().count() + 1 + name.chars().count();
+        let marker = match color {
+            true => paint(marker, output.theme.series_style(records).color),
+            false => marker.to_owned(),
+        };
+
+        if line_width > 0 && line_width + item_width + 2 > width {
+            writeln!(out)?;
+            line_width = 0;
+        }
+
+        if line_width > 0 {
+            write!(out, "  ")?;
+            line_width += 2;
+        }
+
+        write!(out, "{} {}", marker, name)?;
+        line_width += item_width;
+    }
+
+    writeln!(out)?;
+    writeln!(out)
+}
+
+/// The peak throughput of every map and the thread count it was reached at,
+/// best first.
+fn print_peaks(out: &mut impl Write, source: &str, groups: &Groups) -> io::Result<()> {
+    let mut peaks = groups
+        .iter()
+        .filter_map(|(name, records)| {
+            let peak = records.iter().max_by(|a, b| {
+                a.throughput
+                    .total_cmp(&b.throughput)
+                    .then(b.threads.cmp(&a.threads))
+            })?;
+
+            Some((name, peak))
+        })
+        .collect::<Vec<_>>();
+
+    peaks.sort_by(|a, b| b.1.throughput.total_cmp(&a.1.throughput));
+
+    let name_width = peaks
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .fold("Map".len(), usize::max);
+
+    writeln!(out, "{}: Peak throughput", source)?;
+    writeln!(
+        out,
+        "{:<w$}  {:>10}  {:>7}",
+        "Map",
+        "Mop/s",
+        "Threads",
+        w = name_width
+    )?;
+
+    for (name, peak) in peaks {
+        writeln!(
+            out,
+            "{:<w$}  {:>10.1}  {:>7}",
+            name,
+            peak.throughput / 1_000_000.,
+            peak.threads,
+            w = name_width
+        )?;
+    }
+
+    writeln!(out)
+}
+
+/// The width of the terminal in characters, 80 when unknown.
+fn terminal_width() -> usize {
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: `size` is a plain struct owned by this frame, filled by the
+        // kernel on success only.
+        unsafe {
+            let mut size = std::mem::zeroed::<libc::winsize>();
+
+            if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col > 0
+            {
+                return size.ws_col as usize;
+            }
+        }
+    }
+
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(80)
+}
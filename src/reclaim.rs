@@ -0,0 +1,139 @@
+//! Teardown of tables between runs.
+//!
+//! [`Reclaimed`] runs the [`Reclaim`] hook of a table when bustle drops it at
+//! the end of a run and records how long reclamation took, and whether it is
+//! known to be complete. Between runs,
+//! [`settle`] waits for the memory usage of the process to become stable so
+//! that the next run does not pay for the garbage of the previous one.
+
+use std::{
+    mem::ManuallyDrop,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::adapters::Reclaim;
+
+/// How the last table was torn down.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Reclamation {
+    /// Time spent in the [`Reclaim`] hook.
+    pub time: Duration,
+
+    /// Whether every entry retired by the table is known to be freed, false
+    /// when the hook can only reclaim on a best-effort basis.
+    pub complete: bool,
+}
+
+static LAST_RECLAIM: Mutex<Option<Reclamation>> = Mutex::new(None);
+
+static BEST_EFFORT: AtomicBool = AtomicBool::new(false);
+
+/// Take how the last table was torn down.
+pub(crate) fn take() -> Option<Reclamation> {
+    LAST_RECLAIM.lock().unwrap().take()
+}
+
+/// Flag the reclamation of the table being torn down as best-effort, for
+/// [`Reclaim`] hooks unable to tell whether garbage is left.
+pub(crate) fn mark_best_effort() {
+    BEST_EFFORT.store(true, Ordering::Relaxed);
+}
+
+/// A collection that runs its [`Reclaim`] hook when dropped.
+pub(crate) struct Reclaimed<C: Reclaim>(ManuallyDrop<C>);
+
+impl<C> Drop for Reclaimed<C>
+where
+    C: Reclaim,
+{
+    fn drop(&mut self) {
+        BEST_EFFORT.store(false, Ordering::Relaxed);
+        let start = Instant::now();
+
+        // SAFETY: the inner table is never touched again.
+        unsafe { ManuallyDrop::take(&mut self.0) }.reclaim();
+
+        *LAST_RECLAIM.lock().unwrap() = Some(Reclamation {
+            time: start.elapsed(),
+            complete: !BEST_EFFORT.swap(false, Ordering::Relaxed),
+        });
+    }
+}
+
+impl<C> bustle::Collection for Reclaimed<C>
+where
+    C: bustle::Collection + Reclaim,
+{
+    type Handle = C::Handle;
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self(ManuallyDrop::new(C::with_capacity(capacity)))
+    }
+
+    fn pin(&self) -> Self::Handle {
+        self.0.pin()
+    }
+}
+
+/// The interval between two samples of the resident set size.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The number of consecutive stable samples required.
+const STABLE_SAMPLES: usize = 5;
+
+/// Wait until the resident set size stops changing, for at most `max`.
+///
+/// Returns the time spent waiting. Without a way to read the resident set
+/// size, this sleeps for `max`.
+pub(crate) fn settle(max: Duration) -> Duration {
+    let start = Instant::now();
+
+    let Some(mut prev) = resident_set_size() else {
+        thread::sleep(max);
+        return start.elapsed();
+    };
+
+    let mut stable = 0;
+
+    while stable < STABLE_SAMPLES && start.elapsed() < max {
+        thread::sleep(SAMPLE_INTERVAL);
+
+        let Some(rss) = resident_set_size() else {
+            break;
+        };
+
+        // Allow some noise from the allocator and the logger.
+        let tolerance = (prev / 1000).max(1 << 20);
+
+        if rss.abs_diff(prev) <= tolerance {
+            stable += 1;
+        } else {
+            stable = 0;
+        }
+
+        prev = rss;
+    }
+
+    start.elapsed()
+}
+
+#[cfg(target_os = "linux")]
+fn resident_set_size() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages = statm.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+
+    // SAFETY: `sysconf` has no preconditions.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+
+    Some(pages * u64::try_from(page_size).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_set_size() -> Option<u64> {
+    None
+}
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Record {
     pub name: String,
     pub total_ops: u64,
@@ -12,6 +12,48 @@ pub struct Record {
     pub throughput: f64,
     #[serde(with = "timestamp")]
     pub latency: Duration,
+    /// Jain's fairness index of the per-thread throughput.
+    #[serde(default)]
+    pub fairness: Option<f64>,
+    /// The lowest per-thread throughput divided by the highest one.
+    #[serde(default)]
+    pub min_max_ratio: Option<f64>,
+    /// Time between the first and the last thread finishing.
+    #[serde(default, with = "timestamp::option")]
+    pub finish_spread: Option<Duration>,
+    /// The thread placement policy, see `--pin`.
+    #[serde(default)]
+    pub pin: Option<String>,
+    /// The CPUs the worker threads were pinned to, in the kernel list format.
+    #[serde(default)]
+    pub cpus: Option<String>,
+    /// Time spent tearing down the table and reclaiming its garbage.
+    #[serde(default, with = "timestamp::option")]
+    pub reclaim: Option<Duration>,
+    /// Whether every entry retired by the table was known to be freed after
+    /// it was torn down, false when reclamation is best-effort.
+    #[serde(default)]
+    pub reclaim_complete: Option<bool>,
+    /// Time spent waiting for the memory usage to settle after the run, see
+    /// `--gc-sleep-ms`.
+    #[serde(default, with = "timestamp::option")]
+    pub settle: Option<Duration>,
+    /// The global allocator the benchmark ran with, see `--allocator`.
+    #[serde(default)]
+    pub allocator: Option<String>,
+    /// The fraction of lookups finding their key, in the cache workload.
+    #[serde(default)]
+    pub hit_ratio: Option<f64>,
+    /// Where the benchmarked crate was resolved from in `Cargo.lock`, e.g. its
+    /// registry or git commit.
+    #[serde(default)]
+    pub dep_source: Option<String>,
+    /// The checksum of the benchmarked crate, for registry dependencies.
+    #[serde(default)]
+    pub dep_checksum: Option<String>,
+    /// The input the record was read from, when several are merged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 mod timestamp {
@@ -32,4 +74,22 @@ mod timestamp {
     {
         (value.as_nanos() as u64).serialize(serializer)
     }
+
+    pub mod option {
+        use super::*;
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Option::<u64>::deserialize(deserializer).map(|v| v.map(Duration::from_nanos))
+        }
+
+        pub fn serialize<S>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            value.map(|v| v.as_nanos() as u64).serialize(serializer)
+        }
+    }
 }
@@ -0,0 +1,166 @@
+//! A single self-contained HTML report of the results.
+//!
+//! The records are embedded as JSON into a template holding all the styles
+//! and scripts, so the report opens offline and can be shared as one file.
+//! Charts are drawn by the page itself, which keeps hovering, toggling series
+//! and switching scales out of the Rust side.
+
+use std::{error::Error, fmt::Write, fs, path::Path, time::Duration};
+
+use crate::{
+    plot::{hex, split_source, Groups, Theme},
+    record::Record,
+};
+
+const TEMPLATE: &str = include_str!("../assets/report.html");
+
+/// Replaced by the embedded data in the template.
+const DATA_MARKER: &str = "/*DATA*/null";
+
+/// Write the report of every source to `path`.
+pub(crate) fn write_report(
+    path: &Path,
+    title: &str,
+    sources: &[(String, Groups)],
+    theme: &Theme,
+) -> Result<(), Box<dyn Error>> {
+    let html = TEMPLATE
+        .replacen("<!--TITLE-->", &escape_html(title), 2)
+        .replacen(DATA_MARKER, &data(title, sources, theme), 1);
+
+    fs::write(path, html).map_err(|err| format!("cannot write {}: {}", path.display(), err).into())
+}
+
+/// The data of the report, as JSON.
+fn data(title: &str, sources: &[(String, Groups)], theme: &Theme) -> String {
+    let mut json = String::new();
+
+    write!(
+        json,
+        "{{\"title\":{},\"version\":{},\"sources\":[",
+        string(title),
+        string(env!("CARGO_PKG_VERSION"))
+    )
+    .unwrap();
+
+    for (i, (source, groups)) in sources.iter().enumerate() {
+        let (workload, hasher) = split_source(source);
+
+        if i > 0 {
+            json.push(',');
+        }
+
+        write!(
+            json,
+            "{{\"name\":{},\"workload\":{},\"hasher\":{},\"series\":[",
+            string(source),
+            string(workload),
+            string(hasher)
+        )
+        .unwrap();
+
+        for (j, (name, records)) in groups.iter().enumerate() {
+            if j > 0 {
+                json.push(',');
+            }
+
+            write!(
+                json,
+                "{{\"name\":{},\"color\":{},\"records\":[",
+                string(name),
+                string(&hex(theme.series_style(records).color))
+            )
+            .unwrap();
+
+            for (k, record) in records.iter().enumerate() {
+                if k > 0 {
+                    json.push(',');
+                }
+
+                json.push_str(&record_json(record));
+            }
+
+            json.push_str("]}");
+        }
+
+        json.push_str("]}");
+    }
+
+    json.push_str("]}");
+    json
+}
+
+fn record_json(record: &Record) -> String {
+    format!(
+        "{{\"name\":{},\"total_ops\":{},\"threads\":{},\"spent\":{},\"throughput\":{},\
+         \"latency\":{},\"fairness\":{},\"min_max_ratio\":{},\"finish_spread\":{},\
+         \"pin\":{},\"cpus\":{},\"reclaim\":{},\"reclaim_complete\":{},\"settle\":{},\
+         \"allocator\":{},\"hit_ratio\":{}}}",
+        string(&record.name),
+        record.total_ops,
+        record.threads,
+        nanos(Some(record.spent)),
+        number(Some(record.throughput)),
+        nanos(Some(record.latency)),
+        number(record.fairness),
+        number(record.min_max_ratio),
+        nanos(record.finish_spread),
+        record
+            .pin
+            .as_deref()
+            .map_or_else(|| "null".to_owned(), string),
+        record
+            .cpus
+            .as_deref()
+            .map_or_else(|| "null".to_owned(), string),
+        nanos(record.reclaim),
+        record
+            .reclaim_complete
+            .map_or_else(|| "null".to_owned(), |complete| complete.to_string()),
+        nanos(record.settle),
+        record
+            .allocator
+            .as_deref()
+            .map_or_else(|| "null".to_owned(), string),
+        number(record.hit_ratio),
+    )
+}
+
+/// A JSON string, also safe to embed into a `<script>` element.
+fn string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '<' | '>' | '&' => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+/// A JSON number, `null` for missing or non-finite values.
+fn number(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => "null".to_owned(),
+    }
+}
+
+fn nanos(value: Option<Duration>) -> String {
+    value.map_or_else(|| "null".to_owned(), |value| value.as_nanos().to_string())
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
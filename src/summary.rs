@@ -0,0 +1,244 @@
+//! Tables summarizing the results, to keep the README in sync with them.
+
+use std::{error::Error, fmt::Write, fs, ops::Range, path::Path};
+
+use crate::{
+    plot::{split_source, Groups},
+    record::Record,
+};
+
+/// The markers of the README region rewritten by `summarize --readme`.
+const BEGIN: &str = "<!-- summary:begin -->";
+const END: &str = "<!-- summary:end -->";
+
+/// One table per source, with a row per map sorted by peak throughput.
+pub(crate) fn markdown(sources: &[(String, Groups)]) -> String {
+    let mut md = String::new();
+
+    for (i, (source, groups)) in sources.iter().enumerate() {
+        let (workload, hasher) = split_source(source);
+
+        if i > 0 {
+            md.push('\n');
+        }
+
+        match hasher {
+            "" => writeln!(md, "#### {}", workload),
+            hasher => writeln!(md, "#### {} ({})", workload, hasher),
+        }
+        .unwrap();
+
+        md.push_str(
+            "\n| Map | Peak (Mop/s) | Peak threads | At 1 thread (Mop/s) | Max threads | At max threads (Mop/s) | Latency at max threads (ns) |\n\
+             |:--|--:|--:|--:|--:|--:|--:|\n",
+        );
+
+        let mut rows = groups
+            .iter()
+            .filter_map(|(name, records)| Some((name, Row::new(records)?)))
+            .collect::<Vec<_>>();
+
+        rows.sort_by(|a, b| b.1.peak.throughput.total_cmp(&a.1.peak.throughput));
+
+        for (name, row) in rows {
+            writeln!(
+                md,
+                "| {} | {:.1} | {} | {} | {} | {:.1} | {} |",
+                name.replace('|', "\\|"),
+                row.peak.throughput / 1_000_000.,
+                row.peak.threads,
+                row.single.map_or_else(
+                    || "-".to_owned(),
+                    |record| format!("{:.1}", record.throughput / 1_000_000.)
+                ),
+                row.max.threads,
+                row.max.throughput / 1_000_000.,
+                row.max.latency.as_nanos(),
+            )
+            .unwrap();
+        }
+    }
+
+    md
+}
+
+struct Row<'a> {
+    peak: &'a Record,
+    single: Option<&'a Record>,
+    max: &'a Record,
+}
+
+impl<'a> Row<'a> {
+    fn new(records: &'a [Record]) -> Option<Self> {
+        Some(Self {
+            // The fewest threads reaching the peak.
+            peak: records.iter().max_by(|a, b| {
+                a.throughput
+                    .total_cmp(&b.throughput)
+                    .then(b.threads.cmp(&a.threads))
+            })?,
+            single: records.iter().find(|record| record.threads == 1),
+            max: records.iter().max_by_key(|record| record.threads)?,
+        })
+    }
+}
+
+/// Replace the region between the summary markers of the README at `path`.
+///
+/// The markers must be lines of their own, so that the README can mention
+/// them in its prose.
+pub(crate) fn update_readme(path: &Path, summary: &str) -> Result<(), Box<dyn Error>> {
+    let readme = fs::read_to_string(path)
+        .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+
+    let readme = splice(&readme, summary).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    fs::write(path, readme)
+        .map_err(|err| format!("cannot write {}: {}", path.display(), err).into())
+}
+
+/// `readme` with `summary` between its marker lines.
+fn splice(readme: &str, summary: &str) -> Result<String, String> {
+    let begin = marker_line(readme, BEGIN)?;
+    let end = marker_line(readme, END)?;
+
+    if end.start < begin.end {
+        return Err(format!("'{}' before '{}'", END, BEGIN));
+    }
+
+    Ok(format!(
+        "{}\n{}\n{}",
+        &readme[..begin.end],
+        summary,
+        &readme[end.start..]
+    ))
+}
+
+/// The range of the only line of `readme` reading `marker`, its line break
+/// included.
+fn marker_line(readme: &str, marker: &str) -> Result<Range<usize>, String> {
+    let mut lines = readme
+        .split_inclusive('\n')
+        .scan(0, |start, line| {
+            let range = *start..*start + line.len();
+            *start = range.end;
+            Some((range, line))
+        })
+        .filter(|(_, line)| line.trim_end() == marker)
+        .map(|(range, _)| range);
+
+    match (lines.next(), lines.next()) {
+        (Some(range), None) => Ok(range),
+        (None, _) => Err(format!(
+            "no region to rewrite, add '{}' and '{}' lines around it",
+            BEGIN, END
+        )),
+        (Some(_), Some(_)) => Err(format!("several '{}' lines", marker)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use super::*;
+
+    const SUMMARY: &str = "#### Exchange\n\n| Map |\n";
+
+    fn source(name: &str, runs: &[(&str, u32, f64, u64)]) -> (String, Groups) {
+        let mut groups = BTreeMap::<_, Vec<_>>::new();
+
+        for (map, threads, mops, latency_ns) in runs {
+            groups.entry(map.to_string()).or_default().push(Record {
+                name: map.to_string(),
+                threads: *threads,
+                throughput: mops * 1_000_000.,
+                latency: Duration::from_nanos(*latency_ns),
+                ..Default::default()
+            });
+        }
+
+        (name.into(), groups.into())
+    }
+
+    #[test]
+    fn markdown_tables() {
+        let sources = [
+            source(
+                "Exchange.ahash",
+                &[
+                    ("std", 1, 10., 100),
+                    ("std", 4, 5., 800),
+                    ("a|b", 2, 20., 100),
+                    ("a|b", 4, 20., 200),
+                ],
+            ),
+            source("ReadHeavy", &[("std", 1, 1., 1000)]),
+        ];
+
+        assert_eq!(
+            markdown(&sources),
+            "#### Exchange (ahash)\n\n\
+             | Map | Peak (Mop/s) | Peak threads | At 1 thread (Mop/s) | Max threads | At max threads (Mop/s) | Latency at max threads (ns) |\n\
+             |:--|--:|--:|--:|--:|--:|--:|\n\
+             | a\\|b | 20.0 | 2 | - | 4 | 20.0 | 200 |\n\
+             | std | 10.0 | 1 | 10.0 | 4 | 5.0 | 800 |\n\
+             \n\
+             #### ReadHeavy\n\n\
+             | Map | Peak (Mop/s) | Peak threads | At 1 thread (Mop/s) | Max threads | At max threads (Mop/s) | Latency at max threads (ns) |\n\
+             |:--|--:|--:|--:|--:|--:|--:|\n\
+             | std | 1.0 | 1 | 1.0 | 1 | 1.0 | 1000 |\n"
+        );
+    }
+
+    #[test]
+    fn splices_between_the_marker_lines() {
+        let readme = "# Title\n\n<!-- summary:begin -->\nstale\n<!-- summary:end -->\nfooter\n";
+
+        assert_eq!(
+            splice(readme, SUMMARY).unwrap(),
+            "# Title\n\n<!-- summary:begin -->\n\n#### Exchange\n\n| Map |\n\n<!-- summary:end -->\nfooter\n"
+        );
+    }
+
+    #[test]
+    fn splicing_twice_is_stable() {
+        let readme = "<!-- summary:begin -->\n<!-- summary:end -->\n";
+        let once = splice(readme, SUMMARY).unwrap();
+
+        assert_eq!(splice(&once, SUMMARY).unwrap(), once);
+    }
+
+    #[test]
+    fn ignores_markers_quoted_in_prose() {
+        let readme = "Rewrites the region between the `<!-- summary:begin -->` and \
+                      `<!-- summary:end -->` lines.\n\n\
+                      ### Summary\n\n<!-- summary:begin -->\n<!-- summary:end -->\n";
+
+        let spliced = splice(readme, SUMMARY).unwrap();
+
+        assert!(spliced.starts_with(
+            "Rewrites the region between the `<!-- summary:begin -->` and `<!-- summary:end -->` lines.\n"
+        ));
+        assert!(spliced.ends_with(
+            "<!-- summary:begin -->\n\n#### Exchange\n\n| Map |\n\n<!-- summary:end -->\n"
+        ));
+    }
+
+    #[test]
+    fn rejects_missing_duplicated_or_reversed_markers() {
+        assert!(splice("no markers\n", SUMMARY).is_err());
+        assert!(splice(
+            "mentions `<!-- summary:begin -->` and `<!-- summary:end -->`\n",
+            SUMMARY
+        )
+        .is_err());
+        assert!(splice("<!-- summary:begin -->\n", SUMMARY).is_err());
+        assert!(splice(
+            "<!-- summary:begin -->\n<!-- summary:end -->\n<!-- summary:begin -->\n<!-- summary:end -->\n",
+            SUMMARY
+        )
+        .is_err());
+        assert!(splice("<!-- summary:end -->\n<!-- summary:begin -->\n", SUMMARY).is_err());
+    }
+}